@@ -1,5 +1,8 @@
+use std;
 use std::collections::HashMap;
 use std::intrinsics;
+use std::mem;
+use std::time::Instant;
 
 use futures::prelude::*;
 use futures::unsync::oneshot;
@@ -13,8 +16,28 @@ use soma::{Impulse, Soma, Synapse};
 pub enum Constraint<S: Synapse> {
     /// only accept one synapse
     One(S),
+    /// accept one synapse, but do not require it
+    Optional(S),
     /// accept any number of synapses
     Variadic(S),
+    /// accept exactly the given number of synapses
+    RequireN(S, usize),
+    /// accept between a minimum and maximum number of synapses (inclusive)
+    ///
+    /// the minimum must not be above the maximum, or the axon can never start
+    Range(S, usize, usize),
+}
+
+impl<S: Synapse> Constraint<S> {
+    fn synapse(&self) -> S {
+        match self {
+            &Constraint::One(synapse)
+            | &Constraint::Optional(synapse)
+            | &Constraint::Variadic(synapse)
+            | &Constraint::RequireN(synapse, _)
+            | &Constraint::Range(synapse, _, _) => synapse,
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -24,7 +47,124 @@ enum Requirement {
     MetVariadic(Vec<Uuid>),
 }
 
+impl Requirement {
+    fn remove(&mut self, uuid: Uuid) -> bool {
+        match mem::replace(self, Requirement::Unmet) {
+            Requirement::MetOne(other) => if other == uuid {
+                true
+            } else {
+                *self = Requirement::MetOne(other);
+                false
+            },
+            Requirement::MetVariadic(mut somas) => {
+                let len = somas.len();
+                somas.retain(|soma| *soma != uuid);
+
+                let removed = somas.len() != len;
+                *self = Requirement::MetVariadic(somas);
+
+                removed
+            },
+            Requirement::Unmet => false,
+        }
+    }
+
+    fn somas(&self) -> Vec<Uuid> {
+        match self {
+            &Requirement::Unmet => vec![],
+            &Requirement::MetOne(uuid) => vec![uuid],
+            &Requirement::MetVariadic(ref somas) => somas.clone(),
+        }
+    }
+}
+
+fn duplicates<S: Synapse>(constraints: &[Constraint<S>]) -> Vec<S> {
+    let mut seen = vec![];
+    let mut duplicates = vec![];
+
+    for synapse in constraints.iter().map(|constraint| constraint.synapse()) {
+        if seen.contains(&synapse) {
+            if !duplicates.contains(&synapse) {
+                duplicates.push(synapse);
+            }
+        } else {
+            seen.push(synapse);
+        }
+    }
+
+    duplicates
+}
+
+fn invalid_ranges<S: Synapse>(constraints: &[Constraint<S>]) -> Vec<S> {
+    constraints
+        .iter()
+        .filter_map(|constraint| match constraint {
+            &Constraint::Range(synapse, min, max) if min > max => Some(synapse),
+            _ => None,
+        })
+        .collect()
+}
+
+fn requirements<S: Synapse>(
+    constraints: Vec<Constraint<S>>,
+) -> HashMap<S, (Constraint<S>, Requirement)> {
+    constraints
+        .into_iter()
+        .map(|constraint| match constraint {
+            Constraint::One(r) | Constraint::Optional(r) => {
+                (r, (constraint, Requirement::Unmet))
+            },
+            Constraint::Variadic(r)
+            | Constraint::RequireN(r, _)
+            | Constraint::Range(r, _, _) => {
+                (r, (constraint, Requirement::MetVariadic(vec![])))
+            },
+        })
+        .collect()
+}
+
+fn check_requirement<S: Synapse>(
+    synapse: S,
+    constraint: &Constraint<S>,
+    req: &Requirement,
+    side: &str,
+) -> Result<()> {
+    match constraint {
+        &Constraint::One(_) => match req {
+            &Requirement::MetOne(_) => (),
+            &Requirement::Unmet => bail!(ErrorKind::MissingSynapse(format!(
+                "expected {} synapse for {:?}",
+                side, synapse
+            ))),
+            _ => unreachable!(),
+        },
+        &Constraint::Optional(_) => (),
+        &Constraint::Variadic(_) => match req {
+            &Requirement::MetVariadic(_) => (),
+            _ => unreachable!(),
+        },
+        &Constraint::RequireN(_, min) | &Constraint::Range(_, min, _) => {
+            match req {
+                &Requirement::MetVariadic(ref somas) => if somas.len() < min {
+                    bail!(ErrorKind::MissingSynapse(format!(
+                        "expected at least {} {} synapses for {:?}",
+                        min, side, synapse
+                    )))
+                },
+                _ => unreachable!(),
+            }
+        },
+    }
+
+    Ok(())
+}
+
 /// wrap a soma with a set of requirements that will be validated upon startup
+///
+/// AddDendrite, AddTerminal, RemoveDendrite, RemoveTerminal and Start are
+/// checked against the constraints and then passed through to the wrapped
+/// soma, so it can react as soon as a connection is made or removed. Stop is
+/// passed through as is, and probes are answered by the axon itself.
 pub struct Axon<T: Soma + 'static> {
     soma: T,
 
@@ -32,11 +172,23 @@ pub struct Axon<T: Soma + 'static> {
 
     dendrites: HashMap<T::Synapse, (Constraint<T::Synapse>, Requirement)>,
     terminals: HashMap<T::Synapse, (Constraint<T::Synapse>, Requirement)>,
+
+    dendrites_connected: HashMap<(T::Synapse, Uuid), Instant>,
+    terminals_connected: HashMap<(T::Synapse, Uuid), Instant>,
+
+    duplicate_dendrites: Vec<T::Synapse>,
+    duplicate_terminals: Vec<T::Synapse>,
+
+    invalid_dendrite_ranges: Vec<T::Synapse>,
+    invalid_terminal_ranges: Vec<T::Synapse>,
 }
 
 impl<T: Soma + 'static> Axon<T> {
     /// wrap a soma with constraints specified by dendrite and terminal
     /// constraints
+    ///
+    /// an axon with no constraints at all accepts no connections. use
+    /// new_strict to treat that as a mistake.
     pub fn new(
         soma: T,
         dendrites: Vec<Constraint<T::Synapse>>,
@@ -47,36 +199,211 @@ impl<T: Soma + 'static> Axon<T> {
 
             uuid: None,
 
-            dendrites: dendrites
-                .iter()
-                .map(|d| match d {
-                    &Constraint::One(r) => {
-                        (r, (Constraint::One(r), Requirement::Unmet))
-                    },
-                    &Constraint::Variadic(r) => (
-                        r,
-                        (
-                            Constraint::Variadic(r),
-                            Requirement::MetVariadic(vec![]),
-                        ),
-                    ),
-                })
-                .collect(),
-            terminals: terminals
-                .iter()
-                .map(|d| match d {
-                    &Constraint::One(r) => {
-                        (r, (Constraint::One(r), Requirement::Unmet))
-                    },
-                    &Constraint::Variadic(r) => (
-                        r,
-                        (
-                            Constraint::Variadic(r),
-                            Requirement::MetVariadic(vec![]),
-                        ),
-                    ),
-                })
-                .collect(),
+            duplicate_dendrites: duplicates(&dendrites),
+            duplicate_terminals: duplicates(&terminals),
+
+            invalid_dendrite_ranges: invalid_ranges(&dendrites),
+            invalid_terminal_ranges: invalid_ranges(&terminals),
+
+            dendrites: requirements(dendrites),
+            terminals: requirements(terminals),
+
+            dendrites_connected: HashMap::new(),
+            terminals_connected: HashMap::new(),
+        }
+    }
+
+    /// wrap a soma with constraints, failing if none are given or if a range
+    /// has a minimum above its maximum
+    pub fn new_strict(
+        soma: T,
+        dendrites: Vec<Constraint<T::Synapse>>,
+        terminals: Vec<Constraint<T::Synapse>>,
+    ) -> Result<Self> {
+        if dendrites.is_empty() && terminals.is_empty() {
+            bail!(ErrorKind::MissingSynapse(
+                "expected at least one dendrite or terminal constraint".into()
+            ))
+        }
+
+        if let Some(synapse) = invalid_ranges(&dendrites)
+            .into_iter()
+            .chain(invalid_ranges(&terminals))
+            .next()
+        {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "range for {:?} has a minimum above its maximum",
+                synapse
+            )))
+        }
+
+        Ok(Self::new(soma, dendrites, terminals))
+    }
+
+    /// get a reference to the wrapped soma
+    pub fn soma(&self) -> &T {
+        &self.soma
+    }
+
+    /// unwrap the axon and take the soma out of it
+    pub fn into_soma(self) -> T {
+        self.soma
+    }
+
+    /// iterate over the synapses that this axon has dendrite constraints for
+    pub fn dendrite_synapses<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = T::Synapse> + 'a {
+        self.dendrites.keys().cloned()
+    }
+
+    /// iterate over the synapses that this axon has terminal constraints for
+    pub fn terminal_synapses<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = T::Synapse> + 'a {
+        self.terminals.keys().cloned()
+    }
+
+    /// iterate over every soma connected to a dendrite and its synapse
+    pub fn dendrites<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (T::Synapse, Uuid)> + 'a {
+        self.dendrites.iter().flat_map(|(synapse, &(_, ref req))| {
+            req.somas().into_iter().map(move |uuid| (*synapse, uuid))
+        })
+    }
+
+    /// iterate over every soma connected to a terminal and its synapse
+    pub fn terminals<'a>(
+        &'a self,
+    ) -> impl Iterator<Item = (T::Synapse, Uuid)> + 'a {
+        self.terminals.iter().flat_map(|(synapse, &(_, ref req))| {
+            req.somas().into_iter().map(move |uuid| (*synapse, uuid))
+        })
+    }
+
+    /// check if a dendrite or terminal accepts a list of synapses
+    ///
+    /// returns false if the axon has no constraint for the synapse
+    pub fn synapse_is_variadic(&self, synapse: T::Synapse) -> bool {
+        self.dendrites
+            .get(&synapse)
+            .into_iter()
+            .chain(self.terminals.get(&synapse))
+            .any(|&(ref constraint, _)| match constraint {
+                &Constraint::One(_) | &Constraint::Optional(_) => false,
+                _ => true,
+            })
+    }
+
+    /// check if a dendrite or terminal must be connected before startup
+    ///
+    /// returns false if the axon has no constraint for the synapse
+    pub fn synapse_is_required(&self, synapse: T::Synapse) -> bool {
+        self.dendrites
+            .get(&synapse)
+            .into_iter()
+            .chain(self.terminals.get(&synapse))
+            .any(|&(ref constraint, _)| match constraint {
+                &Constraint::One(_) => true,
+                &Constraint::Optional(_) | &Constraint::Variadic(_) => false,
+                &Constraint::RequireN(_, min)
+                | &Constraint::Range(_, min, _) => min > 0,
+            })
+    }
+
+    /// check whether every dendrite and terminal constraint is currently met
+    ///
+    /// returns the synapses that still need connections, along with any that
+    /// were given more than one constraint or a range that cannot be met. this
+    /// performs the same checks as startup, so it can be polled before Start
+    /// arrives.
+    pub fn constraints_satisfied(
+        &self,
+    ) -> std::result::Result<(), Vec<T::Synapse>> {
+        let mut unmet = vec![];
+
+        let invalid = self.duplicate_dendrites
+            .iter()
+            .chain(self.duplicate_terminals.iter())
+            .chain(self.invalid_dendrite_ranges.iter())
+            .chain(self.invalid_terminal_ranges.iter())
+            .cloned();
+
+        let missing = self.dendrites
+            .iter()
+            .filter(|&(synapse, &(ref constraint, ref req))| {
+                check_requirement(*synapse, constraint, req, "dendrite")
+                    .is_err()
+            })
+            .chain(self.terminals.iter().filter(
+                |&(synapse, &(ref constraint, ref req))| {
+                    check_requirement(*synapse, constraint, req, "terminal")
+                        .is_err()
+                },
+            ))
+            .map(|(synapse, _)| *synapse);
+
+        for synapse in invalid.chain(missing) {
+            if !unmet.contains(&synapse) {
+                unmet.push(synapse);
+            }
+        }
+
+        if unmet.is_empty() {
+            Ok(())
+        } else {
+            Err(unmet)
+        }
+    }
+
+    /// get the time at which a soma was connected to a dendrite
+    ///
+    /// returns None if the soma is not connected to the dendrite
+    pub fn dendrite_connected_at(
+        &self,
+        synapse: T::Synapse,
+        uuid: Uuid,
+    ) -> Option<Instant> {
+        self.dendrites_connected.get(&(synapse, uuid)).cloned()
+    }
+
+    /// get the time at which a soma was connected to a terminal
+    ///
+    /// returns None if the soma is not connected to the terminal
+    pub fn terminal_connected_at(
+        &self,
+        synapse: T::Synapse,
+        uuid: Uuid,
+    ) -> Option<Instant> {
+        self.terminals_connected.get(&(synapse, uuid)).cloned()
+    }
+
+    /// count the somas currently connected to a dendrite
+    ///
+    /// fails if the axon has no dendrite constraint for the synapse
+    pub fn dendrite_count(&self, synapse: T::Synapse) -> Result<usize> {
+        if let Some(&(_, ref req)) = self.dendrites.get(&synapse) {
+            Ok(req.somas().len())
+        } else {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "no constraints found for {:?}",
+                synapse
+            )))
+        }
+    }
+
+    /// count the somas currently connected to a terminal
+    ///
+    /// fails if the axon has no terminal constraint for the synapse
+    pub fn terminal_count(&self, synapse: T::Synapse) -> Result<usize> {
+        if let Some(&(_, ref req)) = self.terminals.get(&synapse) {
+            Ok(req.somas().len())
+        } else {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "no constraints found for {:?}",
+                synapse
+            )))
         }
     }
 
@@ -85,18 +412,36 @@ impl<T: Soma + 'static> Axon<T> {
             self.dendrites.get_mut(&synapse)
         {
             match constraint {
-                &mut Constraint::One(_) => match req {
-                    &mut Requirement::Unmet => *req = Requirement::MetOne(uuid),
-                    &mut Requirement::MetOne(_) => {
-                        bail!(ErrorKind::InvalidSynapse(format!(
-                            "expected only one dendrite for {:?}",
-                            synapse
-                        )))
+                &mut Constraint::One(_) | &mut Constraint::Optional(_) => {
+                    match req {
+                        &mut Requirement::Unmet => {
+                            *req = Requirement::MetOne(uuid)
+                        },
+                        &mut Requirement::MetOne(_) => {
+                            bail!(ErrorKind::InvalidSynapse(format!(
+                                "expected only one dendrite for {:?}",
+                                synapse
+                            )))
+                        },
+                        _ => unreachable!(),
+                    }
+                },
+                &mut Constraint::Variadic(_) => match req {
+                    &mut Requirement::MetVariadic(ref mut dendrites) => {
+                        dendrites.push(uuid);
                     },
                     _ => unreachable!(),
                 },
-                &mut Constraint::Variadic(_) => match req {
+                &mut Constraint::RequireN(_, max)
+                | &mut Constraint::Range(_, _, max) => match req {
                     &mut Requirement::MetVariadic(ref mut dendrites) => {
+                        if dendrites.len() >= max {
+                            bail!(ErrorKind::InvalidSynapse(format!(
+                                "expected at most {} dendrites for {:?}",
+                                max, synapse
+                            )))
+                        }
+
                         dendrites.push(uuid);
                     },
                     _ => unreachable!(),
@@ -109,6 +454,8 @@ impl<T: Soma + 'static> Axon<T> {
             )))
         }
 
+        self.dendrites_connected.insert((synapse, uuid), Instant::now());
+
         Ok(())
     }
 
@@ -117,18 +464,36 @@ impl<T: Soma + 'static> Axon<T> {
             self.terminals.get_mut(&synapse)
         {
             match constraint {
-                &mut Constraint::One(_) => match req {
-                    &mut Requirement::Unmet => *req = Requirement::MetOne(uuid),
-                    &mut Requirement::MetOne(_) => {
-                        bail!(ErrorKind::InvalidSynapse(format!(
-                            "expected only one terminal for {:?}",
-                            synapse
-                        )))
+                &mut Constraint::One(_) | &mut Constraint::Optional(_) => {
+                    match req {
+                        &mut Requirement::Unmet => {
+                            *req = Requirement::MetOne(uuid)
+                        },
+                        &mut Requirement::MetOne(_) => {
+                            bail!(ErrorKind::InvalidSynapse(format!(
+                                "expected only one terminal for {:?}",
+                                synapse
+                            )))
+                        },
+                        _ => unreachable!(),
+                    }
+                },
+                &mut Constraint::Variadic(_) => match req {
+                    &mut Requirement::MetVariadic(ref mut terminals) => {
+                        terminals.push(uuid);
                     },
                     _ => unreachable!(),
                 },
-                &mut Constraint::Variadic(_) => match req {
+                &mut Constraint::RequireN(_, max)
+                | &mut Constraint::Range(_, _, max) => match req {
                     &mut Requirement::MetVariadic(ref mut terminals) => {
+                        if terminals.len() >= max {
+                            bail!(ErrorKind::InvalidSynapse(format!(
+                                "expected at most {} terminals for {:?}",
+                                max, synapse
+                            )))
+                        }
+
                         terminals.push(uuid);
                     },
                     _ => unreachable!(),
@@ -141,42 +506,112 @@ impl<T: Soma + 'static> Axon<T> {
             )))
         }
 
+        self.terminals_connected.insert((synapse, uuid), Instant::now());
+
+        Ok(())
+    }
+
+    fn remove_dendrite(
+        &mut self,
+        uuid: Uuid,
+        synapse: T::Synapse,
+    ) -> Result<()> {
+        if let Some(&mut (ref constraint, ref mut req)) =
+            self.dendrites.get_mut(&synapse)
+        {
+            if !req.remove(uuid) {
+                bail!(ErrorKind::InvalidSynapse(format!(
+                    "no dendrite for {:?} from {}",
+                    synapse, uuid
+                )))
+            }
+
+            self.dendrites_connected.remove(&(synapse, uuid));
+
+            // a started soma relies on its required synapses, so losing one
+            // is an error rather than something to notice later
+            if self.uuid.is_some() {
+                check_requirement(synapse, constraint, req, "dendrite")?;
+            }
+        } else {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "no constraints found for {:?}",
+                synapse
+            )))
+        }
+
+        Ok(())
+    }
+
+    fn remove_terminal(
+        &mut self,
+        uuid: Uuid,
+        synapse: T::Synapse,
+    ) -> Result<()> {
+        if let Some(&mut (ref constraint, ref mut req)) =
+            self.terminals.get_mut(&synapse)
+        {
+            if !req.remove(uuid) {
+                bail!(ErrorKind::InvalidSynapse(format!(
+                    "no terminal for {:?} to {}",
+                    synapse, uuid
+                )))
+            }
+
+            self.terminals_connected.remove(&(synapse, uuid));
+
+            // a started soma relies on its required synapses, so losing one
+            // is an error rather than something to notice later
+            if self.uuid.is_some() {
+                check_requirement(synapse, constraint, req, "terminal")?;
+            }
+        } else {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "no constraints found for {:?}",
+                synapse
+            )))
+        }
+
         Ok(())
     }
 
     fn start(&mut self, uuid: Uuid) -> Result<()> {
         self.uuid = Some(uuid);
 
+        if let Some(synapse) = self.duplicate_dendrites.first() {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "dendrite synapse {:?} specified more than once",
+                *synapse
+            )))
+        }
+
+        if let Some(synapse) = self.duplicate_terminals.first() {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "terminal synapse {:?} specified more than once",
+                *synapse
+            )))
+        }
+
+        if let Some(synapse) = self.invalid_dendrite_ranges.first() {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "dendrite range for {:?} has a minimum above its maximum",
+                *synapse
+            )))
+        }
+
+        if let Some(synapse) = self.invalid_terminal_ranges.first() {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "terminal range for {:?} has a minimum above its maximum",
+                *synapse
+            )))
+        }
+
         for (synapse, &(ref constraint, ref req)) in &self.dendrites {
-            match constraint {
-                &Constraint::One(_) => match req {
-                    &Requirement::MetOne(_) => (),
-                    &Requirement::Unmet => bail!(ErrorKind::MissingSynapse(
-                        format!("expected dendrite synapse for {:?}", *synapse)
-                    )),
-                    _ => unreachable!(),
-                },
-                &Constraint::Variadic(_) => match req {
-                    &Requirement::MetVariadic(_) => (),
-                    _ => unreachable!(),
-                },
-            }
+            check_requirement(*synapse, constraint, req, "dendrite")?;
         }
 
         for (synapse, &(ref constraint, ref req)) in &self.terminals {
-            match constraint {
-                &Constraint::One(_) => match req {
-                    &Requirement::MetOne(_) => (),
-                    &Requirement::Unmet => bail!(ErrorKind::MissingSynapse(
-                        format!("expected terminal synapse for {:?}", *synapse)
-                    )),
-                    _ => unreachable!(),
-                },
-                &Constraint::Variadic(_) => match req {
-                    &Requirement::MetVariadic(_) => (),
-                    _ => unreachable!(),
-                },
-            }
+            check_requirement(*synapse, constraint, req, "terminal")?;
         }
 
         Ok(())
@@ -206,46 +641,76 @@ impl<T: Soma + 'static> Soma for Axon<T> {
     fn probe(self, _settings: probe::Settings) -> Result<(Self, SomaData)> {
         let terminals = self.terminals
             .iter()
-            .map(|(synapse, &(ref constraint, ref requirement))| {
+            .filter_map(|(synapse, &(ref constraint, ref requirement))| {
                 match constraint {
-                    &Constraint::One(_) => ConstraintData::One {
-                        variant: format!("{:?}", *synapse),
-                        soma: match requirement {
-                            &Requirement::MetOne(ref uuid) => *uuid,
-                            _ => panic!("axon failed to validate"),
+                    // only unmet if probed before startup, since losing a
+                    // required synapse afterwards fails the axon
+                    &Constraint::One(_) => match requirement {
+                        &Requirement::MetOne(ref uuid) => {
+                            Some(ConstraintData::One {
+                                variant: format!("{:?}", *synapse),
+                                soma: *uuid,
+                            })
                         },
+                        _ => None,
                     },
-                    &Constraint::Variadic(_) => ConstraintData::Variadic {
-                        variant: format!("{:?}", *synapse),
-                        somas: match requirement {
-                            &Requirement::MetVariadic(ref somas) => {
-                                somas.clone()
+                    &Constraint::Optional(_) => {
+                        Some(ConstraintData::Optional {
+                            variant: format!("{:?}", *synapse),
+                            soma: match requirement {
+                                &Requirement::MetOne(ref uuid) => Some(*uuid),
+                                _ => None,
                             },
-                            _ => unreachable!(),
-                        },
+                        })
+                    },
+                    _ => {
+                        Some(ConstraintData::Variadic {
+                            variant: format!("{:?}", *synapse),
+                            somas: match requirement {
+                                &Requirement::MetVariadic(ref somas) => {
+                                    somas.clone()
+                                },
+                                _ => unreachable!(),
+                            },
+                        })
                     },
                 }
             })
             .collect();
         let dendrites = self.dendrites
             .iter()
-            .map(|(synapse, &(ref constraint, ref requirement))| {
+            .filter_map(|(synapse, &(ref constraint, ref requirement))| {
                 match constraint {
-                    &Constraint::One(_) => ConstraintData::One {
-                        variant: format!("{:?}", *synapse),
-                        soma: match requirement {
-                            &Requirement::MetOne(ref uuid) => *uuid,
-                            _ => panic!("axon failed to validate"),
+                    // only unmet if probed before startup, since losing a
+                    // required synapse afterwards fails the axon
+                    &Constraint::One(_) => match requirement {
+                        &Requirement::MetOne(ref uuid) => {
+                            Some(ConstraintData::One {
+                                variant: format!("{:?}", *synapse),
+                                soma: *uuid,
+                            })
                         },
+                        _ => None,
                     },
-                    &Constraint::Variadic(_) => ConstraintData::Variadic {
-                        variant: format!("{:?}", *synapse),
-                        somas: match requirement {
-                            &Requirement::MetVariadic(ref somas) => {
-                                somas.clone()
+                    &Constraint::Optional(_) => {
+                        Some(ConstraintData::Optional {
+                            variant: format!("{:?}", *synapse),
+                            soma: match requirement {
+                                &Requirement::MetOne(ref uuid) => Some(*uuid),
+                                _ => None,
                             },
-                            _ => unreachable!(),
-                        },
+                        })
+                    },
+                    _ => {
+                        Some(ConstraintData::Variadic {
+                            variant: format!("{:?}", *synapse),
+                            somas: match requirement {
+                                &Requirement::MetVariadic(ref somas) => {
+                                    somas.clone()
+                                },
+                                _ => unreachable!(),
+                            },
+                        })
                     },
                 }
             })
@@ -283,6 +748,22 @@ impl<T: Soma + 'static> Soma for Axon<T> {
 
                 Ok(self)
             },
+            Impulse::RemoveDendrite(uuid, synapse) => {
+                self.remove_dendrite(uuid, synapse)?;
+
+                self.soma =
+                    await!(self.soma.update(imp)).map_err(|e| e.into())?;
+
+                Ok(self)
+            },
+            Impulse::RemoveTerminal(uuid, synapse) => {
+                self.remove_terminal(uuid, synapse)?;
+
+                self.soma =
+                    await!(self.soma.update(imp)).map_err(|e| e.into())?;
+
+                Ok(self)
+            },
             Impulse::Start(uuid, _, _) => {
                 self.start(uuid)?;
 
@@ -296,9 +777,14 @@ impl<T: Soma + 'static> Soma for Axon<T> {
                 await!(self.perform_probe(settings, tx))
             },
 
-            Impulse::Stop | Impulse::Error(_) => {
-                bail!("unexpected impulse in axon")
+            Impulse::Stop => {
+                self.soma =
+                    await!(self.soma.update(imp)).map_err(|e| e.into())?;
+
+                Ok(self)
             },
+
+            Impulse::Error(_) => bail!("unexpected impulse in axon"),
             //_ => await!(self.soma.update(imp))?,
         }
     }
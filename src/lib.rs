@@ -6,6 +6,8 @@
 #[macro_use]
 extern crate error_chain;
 #[macro_use]
+extern crate log;
+#[macro_use]
 extern crate serde_derive;
 
 extern crate bytes;
@@ -22,6 +24,8 @@ extern crate hyper;
 extern crate open;
 
 mod axon;
+#[allow(dead_code)]
+mod dot;
 mod organelle;
 mod soma;
 
@@ -33,9 +37,12 @@ pub mod visualizer;
 pub mod probe;
 
 pub use axon::{Axon, Constraint};
-pub use organelle::Organelle;
+pub use organelle::{
+    Organelle, OrganelleBuilder, OrganelleSettings, OrganelleTemplate,
+    TopologyEvent,
+};
 pub use probe::{ConstraintData, SomaData};
-pub use soma::{Impulse, Soma, Synapse};
+pub use soma::{Impulse, Middleware, Soma, Synapse};
 
 /// organelle error
 error_chain! {
@@ -74,6 +81,12 @@ error_chain! {
             description("missing synapse"),
             display("invalid synapse - {}", msg)
         }
+
+        /// somas are connected in a cycle when they were required not to be
+        Cycle(msg: String) {
+            description("somas connected in a cycle"),
+            display("somas connected in a cycle - {}", msg)
+        }
     }
 }
 
@@ -1,18 +1,68 @@
 use std;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::intrinsics;
 use std::mem;
 
-use futures::future;
+use bytes::BufMut;
+
+use futures::future::{self, Either};
 use futures::prelude::*;
 use futures::stream;
 use futures::unsync::{mpsc, oneshot};
 use tokio_core::reactor;
 use uuid::Uuid;
 
-use super::{Error, Result};
+use super::{Error, ErrorKind, Result};
+use dot;
 use probe::{self, SomaData};
-use soma::{Impulse, Soma, Synapse};
+use soma::{Impulse, Middleware, Soma, Synapse};
+
+/// organelle settings
+#[derive(Debug, Clone)]
+pub struct OrganelleSettings {
+    soma_buffer: usize,
+    queue_buffer: usize,
+}
+
+impl OrganelleSettings {
+    /// set the number of impulses that can be buffered for each soma
+    pub fn soma_buffer(self, size: usize) -> Self {
+        Self {
+            soma_buffer: size,
+            ..self
+        }
+    }
+
+    /// set the number of impulses that can be buffered for the organelle
+    pub fn queue_buffer(self, size: usize) -> Self {
+        Self {
+            queue_buffer: size,
+            ..self
+        }
+    }
+}
+
+impl Default for OrganelleSettings {
+    fn default() -> Self {
+        Self {
+            soma_buffer: 10,
+            queue_buffer: 100,
+        }
+    }
+}
+
+/// a change made to the somas or synapses of an organelle
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TopologyEvent<S: Synapse> {
+    /// a soma was added to the organelle
+    SomaAdded(Uuid),
+    /// a soma was removed from the organelle
+    SomaRemoved(Uuid),
+    /// two somas were connected with the given synapse
+    Connected(Uuid, Uuid, S),
+    /// two somas were disconnected from the given synapse
+    Disconnected(Uuid, Uuid, S),
+}
 
 /// a soma designed to facilitate connections between other somas
 ///
@@ -25,6 +75,7 @@ where
     T: Soma,
 {
     handle: reactor::Handle,
+    settings: OrganelleSettings,
 
     uuid: Option<Uuid>,
 
@@ -33,23 +84,68 @@ where
     main_rx: Option<mpsc::Receiver<Impulse<T::Synapse>>>,
 
     somas: HashMap<Uuid, mpsc::Sender<Impulse<T::Synapse>>>,
+    stopped: HashMap<Uuid, oneshot::Receiver<Result<()>>>,
+    connections: Vec<(Uuid, Uuid, T::Synapse)>,
+    names: HashMap<Uuid, String>,
+
+    acyclic: bool,
+
+    uuid_generator: Box<FnMut() -> Uuid>,
+
+    observers: Vec<Box<Fn(TopologyEvent<T::Synapse>)>>,
 }
 
 impl<T: Soma + 'static> Organelle<T> {
     /// create a new organelle
     pub fn new(main: T, handle: reactor::Handle) -> Self {
-        let (tx, rx) = mpsc::channel(100);
+        Self::with_settings(main, OrganelleSettings::default(), handle)
+    }
+
+    /// create a new organelle with the given settings
+    pub fn with_settings(
+        main: T,
+        settings: OrganelleSettings,
+        handle: reactor::Handle,
+    ) -> Self {
+        Self::with_uuid_generator(main, settings, handle, Uuid::new_v4)
+    }
+
+    /// create a new organelle that uses the given function to create uuids
+    ///
+    /// this is useful for tests that need the same uuids on every run, such
+    /// as comparing DOT output. the generator must never repeat a uuid, and
+    /// adding a soma panics if it does.
+    pub fn with_uuid_generator<F>(
+        main: T,
+        settings: OrganelleSettings,
+        handle: reactor::Handle,
+        generator: F,
+    ) -> Self
+    where
+        F: FnMut() -> Uuid + 'static,
+    {
+        let (tx, rx) = mpsc::channel(settings.queue_buffer);
 
         let mut organelle = Self {
             handle: handle,
+            settings: settings,
 
             uuid: None,
 
-            main: Uuid::new_v4(),
+            main: Uuid::nil(),
             main_tx: tx,
             main_rx: Some(rx),
 
             somas: HashMap::new(),
+            stopped: HashMap::new(),
+            connections: vec![],
+            names: HashMap::new(),
+
+            acyclic: false,
+
+            uuid_generator: Box::new(generator),
+
+            observers: vec![],
         };
 
         let main = organelle.add_soma(main);
@@ -63,6 +159,139 @@ impl<T: Soma + 'static> Organelle<T> {
         self.main
     }
 
+    /// get a sender for impulses from outside of the organelle
+    ///
+    /// impulses sent here are handled as if they came from one of the somas,
+    /// so sending Stop will shut the organelle down. anything sent before the
+    /// organelle starts is queued until it does, which means synapses cannot
+    /// be added or removed this way. use connect and disconnect instead.
+    pub fn impulse_sender(&self) -> mpsc::Sender<Impulse<T::Synapse>> {
+        self.main_tx.clone()
+    }
+
+    /// get the name given to a soma with add_soma_named
+    pub fn name(&self, uuid: Uuid) -> Option<&str> {
+        self.names.get(&uuid).map(|name| name.as_str())
+    }
+
+    fn label(&self, uuid: Uuid) -> String {
+        match self.name(uuid) {
+            Some(name) => format!("{} ({})", name, uuid),
+            None => uuid.to_string(),
+        }
+    }
+
+    /// iterate over the uuids of every soma in the organelle
+    ///
+    /// the nucleus is included, but in no particular position
+    pub fn uuids<'a>(&'a self) -> impl Iterator<Item = Uuid> + 'a {
+        self.somas.keys().cloned()
+    }
+
+    /// find every soma that holds a terminal for the given synapse
+    pub fn somas_with_terminal(&self, synapse: T::Synapse) -> Vec<Uuid> {
+        let mut somas = vec![];
+
+        for &(dendrite, _, other) in &self.connections {
+            if other == synapse && !somas.contains(&dendrite) {
+                somas.push(dendrite);
+            }
+        }
+
+        somas
+    }
+
+    /// find every soma that holds a dendrite for the given synapse
+    pub fn somas_with_dendrite(&self, synapse: T::Synapse) -> Vec<Uuid> {
+        let mut somas = vec![];
+
+        for &(_, terminal, other) in &self.connections {
+            if other == synapse && !somas.contains(&terminal) {
+                somas.push(terminal);
+            }
+        }
+
+        somas
+    }
+
+    /// render the somas and synapses of the organelle in the DOT language
+    ///
+    /// each synapse is drawn as an edge from the soma with the terminal to the
+    /// soma with the dendrite, and the nucleus is drawn as a double octagon.
+    /// named somas are labelled with their name as well as their uuid.
+    pub fn to_dot(&self) -> String {
+        let mut uuids: Vec<_> = self.uuids().collect();
+        uuids.sort();
+
+        let mut graph = dot::SubGraph::new();
+
+        for uuid in uuids {
+            let mut node = dot::Node::new(dot::Id::quoted(uuid.to_string()));
+
+            if let Some(name) = self.name(uuid) {
+                node = node.add(dot::Attribute::new(
+                    dot::Id::ident("label"),
+                    dot::Id::quoted(format!("{} ({})", name, uuid)),
+                ));
+            }
+
+            if uuid == self.main {
+                node = node.add(dot::Attribute::new(
+                    dot::Id::ident("shape"),
+                    dot::Id::ident("doubleoctagon"),
+                ));
+            }
+
+            graph = graph.add(node);
+        }
+
+        for &(dendrite, terminal, synapse) in &self.connections {
+            // wrap each edge so that it gets its own label
+            graph = graph.add(
+                dot::SubGraph::new()
+                    .add(dot::Selector::edge().add(dot::Attribute::new(
+                        dot::Id::ident("label"),
+                        dot::Id::quoted(format!("{:?}", synapse)),
+                    )))
+                    .add(
+                        dot::NodeId::new(dot::Id::quoted(dendrite.to_string()))
+                            .connect(
+                                dot::EdgeOp::Directed,
+                                dot::NodeId::new(dot::Id::quoted(
+                                    terminal.to_string(),
+                                )),
+                            ),
+                    ),
+            );
+        }
+
+        let mut writer = Vec::new().writer();
+
+        dot::Dot::DiGraph(graph)
+            .render(&mut writer)
+            .expect("writing to a vec cannot fail");
+
+        String::from_utf8(writer.into_inner())
+            .expect("rendered dot is built from strings")
+    }
+
+    /// observe changes to the topology of the organelle
+    ///
+    /// the nucleus is added before any observer can be registered, so it will
+    /// never be reported as a SomaAdded event.
+    pub fn observe_topology<F>(&mut self, f: F)
+    where
+        F: Fn(TopologyEvent<T::Synapse>) + 'static,
+    {
+        self.observers.push(Box::new(f));
+    }
+
+    fn notify(&self, event: TopologyEvent<T::Synapse>) {
+        for observer in &self.observers {
+            observer(event);
+        }
+    }
+
     fn create_soma_channel<R>(&mut self) -> (Uuid, mpsc::Receiver<Impulse<R>>)
     where
         R: Synapse + From<T::Synapse> + Into<T::Synapse> + 'static,
@@ -73,9 +302,16 @@ impl<T: Soma + 'static> Organelle<T> {
             + Into<<T::Synapse as Synapse>::Terminal>
             + 'static,
     {
-        let uuid = Uuid::new_v4();
+        let uuid = (self.uuid_generator)();
+
+        assert!(
+            !self.somas.contains_key(&uuid),
+            "uuid generator returned {} more than once",
+            uuid
+        );
 
-        let (tx, rx) = mpsc::channel::<Impulse<T::Synapse>>(10);
+        let (tx, rx) =
+            mpsc::channel::<Impulse<T::Synapse>>(self.settings.soma_buffer);
 
         let (soma_tx, soma_rx) = mpsc::channel::<Impulse<R>>(1);
 
@@ -111,17 +347,74 @@ impl<T: Soma + 'static> Organelle<T> {
     fn run_soma<U: Soma + 'static>(
         mut soma: U,
         soma_rx: mpsc::Receiver<Impulse<U::Synapse>>,
-    ) -> std::result::Result<(), Error> {
+        mut middleware: Vec<Box<Middleware<U::Synapse>>>,
+    ) -> std::result::Result<Result<()>, Error> {
         #[async]
         for imp in soma_rx.map_err(|_| -> Error { unreachable!() }) {
-            soma = await!(soma.update(imp)).map_err(|e| e.into())?;
+            if let Impulse::Stop = imp {
+                // stop skips the middleware so the soma always gets a chance
+                // to finish up before its loop ends. whatever it returns goes
+                // to whoever stopped it
+                return Ok(await!(soma.update(Impulse::Stop))
+                    .map(|_| ())
+                    .map_err(|e| e.into()));
+            }
+
+            let imp = middleware
+                .iter_mut()
+                .fold(Some(imp), |imp, m| imp.and_then(|imp| m.process(imp)));
+
+            if let Some(imp) = imp {
+                soma = await!(soma.update(imp)).map_err(|e| e.into())?
+            }
         }
 
-        Ok(())
+        Ok(Ok(()))
     }
 
     /// add a soma to the organelle
     pub fn add_soma<U: Soma + 'static>(&mut self, soma: U) -> Uuid
+    where
+        U::Synapse: From<T::Synapse> + Into<T::Synapse>,
+        <U::Synapse as Synapse>::Dendrite: From<<T::Synapse as Synapse>::Dendrite>
+            + Into<<T::Synapse as Synapse>::Dendrite>,
+        <U::Synapse as Synapse>::Terminal: From<<T::Synapse as Synapse>::Terminal>
+            + Into<<T::Synapse as Synapse>::Terminal>,
+    {
+        self.add_soma_with_middleware(soma, vec![])
+    }
+
+    /// add a soma to the organelle with a name to use in diagnostics
+    pub fn add_soma_named<U: Soma + 'static>(
+        &mut self,
+        soma: U,
+        name: &str,
+    ) -> Uuid
+    where
+        U::Synapse: From<T::Synapse> + Into<T::Synapse>,
+        <U::Synapse as Synapse>::Dendrite: From<<T::Synapse as Synapse>::Dendrite>
+            + Into<<T::Synapse as Synapse>::Dendrite>,
+        <U::Synapse as Synapse>::Terminal: From<<T::Synapse as Synapse>::Terminal>
+            + Into<<T::Synapse as Synapse>::Terminal>,
+    {
+        let uuid = self.add_soma(soma);
+
+        self.names.insert(uuid, name.to_string());
+
+        uuid
+    }
+
+    /// add a soma whose impulses pass through a chain of middleware first
+    ///
+    /// middleware is applied in order, and any step can drop the impulse
+    /// before it reaches the soma. Stop is the exception, since the organelle
+    /// waits for every soma to answer it, so it never passes through the
+    /// middleware.
+    pub fn add_soma_with_middleware<U: Soma + 'static>(
+        &mut self,
+        soma: U,
+        middleware: Vec<Box<Middleware<U::Synapse>>>,
+    ) -> Uuid
     where
         U::Synapse: From<T::Synapse> + Into<T::Synapse>,
         <U::Synapse as Synapse>::Dendrite: From<<T::Synapse as Synapse>::Dendrite>
@@ -130,37 +423,197 @@ impl<T: Soma + 'static> Organelle<T> {
             + Into<<T::Synapse as Synapse>::Terminal>,
     {
         let (uuid, soma_rx) = self.create_soma_channel::<U::Synapse>();
+        let (stopped_tx, stopped_rx) = oneshot::channel();
 
         let main_tx = self.main_tx.clone();
 
-        self.handle
-            .spawn(Self::run_soma(soma, soma_rx).or_else(move |e| {
-                main_tx
-                    .send(Impulse::Error(e.into()))
-                    .map(|_| ())
-                    .map_err(|_| ())
-            }));
+        self.handle.spawn(
+            Self::run_soma(soma, soma_rx, middleware)
+                .or_else(move |e| {
+                    main_tx
+                        .send(Impulse::Error(e.into()))
+                        .then(|_| Ok(Ok(())))
+                })
+                .and_then(move |stopped| {
+                    if let Err(_) = stopped_tx.send(stopped) {
+                        // nobody is waiting for the soma to stop
+                    }
+
+                    future::ok::<(), ()>(())
+                }),
+        );
+
+        self.stopped.insert(uuid, stopped_rx);
+
+        self.notify(TopologyEvent::SomaAdded(uuid));
 
         uuid
     }
 
+    /// remove a soma from the organelle
+    ///
+    /// every soma connected to it is told to drop the synapse, and the soma
+    /// stops once its channel is closed. the nucleus cannot be removed.
+    ///
+    /// run consumes the organelle, so somas can only be removed before it
+    /// starts.
+    pub fn remove_soma(&mut self, uuid: Uuid) -> Result<()> {
+        if uuid == self.main {
+            bail!("unable to remove the nucleus of an organelle")
+        }
+        if !self.somas.contains_key(&uuid) {
+            bail!("unable to find soma {}", uuid)
+        }
+
+        let (removed, connections): (Vec<_>, Vec<_>) = self.connections
+            .drain(..)
+            .partition(|&(dendrite, terminal, _)| {
+                dendrite == uuid || terminal == uuid
+            });
+        self.connections = connections;
+
+        for (dendrite, terminal, synapse) in removed {
+            if dendrite != uuid {
+                self.remove_terminal(terminal, dendrite, synapse)?;
+            }
+            if terminal != uuid {
+                self.remove_dendrite(dendrite, terminal, synapse)?;
+            }
+
+            self.notify(TopologyEvent::Disconnected(
+                dendrite,
+                terminal,
+                synapse,
+            ));
+        }
+
+        self.somas.remove(&uuid);
+        self.stopped.remove(&uuid);
+        self.names.remove(&uuid);
+
+        self.notify(TopologyEvent::SomaRemoved(uuid));
+
+        Ok(())
+    }
+
     /// connect two somas together using the specified synapse
+    ///
+    /// fails if either soma is not part of the organelle or if the somas are
+    /// already connected with this synapse. the same pair of somas can be
+    /// connected with several different synapses, and each one is delivered
+    /// and disconnected separately.
     pub fn connect(
-        &self,
+        &mut self,
         dendrite: Uuid,
         terminal: Uuid,
         synapse: T::Synapse,
     ) -> Result<()> {
+        for uuid in &[dendrite, terminal] {
+            if !self.somas.contains_key(uuid) {
+                bail!(
+                    "unable to connect {} to {} with {:?} - soma {} not found",
+                    dendrite,
+                    terminal,
+                    synapse,
+                    uuid
+                )
+            }
+        }
+        if self.connections.contains(&(dendrite, terminal, synapse)) {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "{:?} already connects {} to {}",
+                synapse, dendrite, terminal
+            )))
+        }
+
         let (tx, rx) = synapse.synapse();
 
         self.add_terminal((terminal, tx), dendrite, synapse)?;
         self.add_dendrite((dendrite, rx), terminal, synapse)?;
 
+        self.connections.push((dendrite, terminal, synapse));
+
+        self.notify(TopologyEvent::Connected(dendrite, terminal, synapse));
+
         Ok(())
     }
 
-    /// send a dendrite to the specified soma
-    pub fn add_dendrite(
+    /// connect two somas together in both directions using the same synapse
+    ///
+    /// each soma receives both a dendrite and a terminal for the other.
+    pub fn connect_bidirectional(
+        &mut self,
+        a: Uuid,
+        b: Uuid,
+        synapse: T::Synapse,
+    ) -> Result<()> {
+        self.connect(a, b, synapse)?;
+        self.connect(b, a, synapse)
+    }
+
+    /// refuse to start if the somas are connected in a cycle
+    ///
+    /// organelles allow cycles by default so that somas can form feedback
+    /// loops. connect_bidirectional always forms a cycle between its two
+    /// somas, so it cannot be used in an organelle that requires this.
+    pub fn require_acyclic(&mut self) {
+        self.acyclic = true;
+    }
+
+    fn find_cycle(&self) -> Option<Vec<Uuid>> {
+        fn visit(
+            uuid: Uuid,
+            edges: &HashMap<Uuid, Vec<Uuid>>,
+            path: &mut Vec<Uuid>,
+            visited: &mut HashSet<Uuid>,
+        ) -> Option<Vec<Uuid>> {
+            if let Some(i) = path.iter().position(|other| *other == uuid) {
+                let mut cycle = path[i..].to_vec();
+                cycle.push(uuid);
+
+                return Some(cycle);
+            }
+
+            if visited.contains(&uuid) {
+                return None;
+            }
+
+            path.push(uuid);
+
+            for next in edges.get(&uuid).into_iter().flat_map(|v| v.iter()) {
+                if let Some(cycle) = visit(*next, edges, path, visited) {
+                    return Some(cycle);
+                }
+            }
+
+            path.pop();
+            visited.insert(uuid);
+
+            None
+        }
+
+        let mut edges = HashMap::new();
+
+        for &(dendrite, terminal, _) in &self.connections {
+            edges.entry(dendrite).or_insert_with(Vec::new).push(terminal);
+        }
+
+        let mut uuids: Vec<_> = self.uuids().collect();
+        uuids.sort();
+
+        let mut visited = HashSet::new();
+
+        for uuid in uuids {
+            if let Some(cycle) = visit(uuid, &edges, &mut vec![], &mut visited)
+            {
+                return Some(cycle);
+            }
+        }
+
+        None
+    }
+
+    fn add_dendrite(
         &self,
         dendrite: (Uuid, <T::Synapse as Synapse>::Dendrite),
         terminal: Uuid,
@@ -169,23 +622,28 @@ impl<T: Soma + 'static> Organelle<T> {
         let terminal_sender = if let Some(sender) = self.somas.get(&terminal) {
             sender.clone()
         } else {
-            bail!("unable to find terminal")
+            bail!("unable to find terminal {}", terminal)
         };
 
+        let label = self.label(terminal);
+
         self.handle.spawn(
             terminal_sender
                 .send(Impulse::AddDendrite(dendrite.0, synapse, dendrite.1))
                 .map(|_| ())
-                .map_err(|_| {
-                    eprintln!("unable to add dendrite");
+                .map_err(move |_| {
+                    error!(
+                        target: "organelle::soma",
+                        "unable to add dendrite to {}",
+                        label
+                    );
                 }),
         );
 
         Ok(())
     }
 
-    /// send a terminal to the specified soma
-    pub fn add_terminal(
+    fn add_terminal(
         &self,
         terminal: (Uuid, <T::Synapse as Synapse>::Terminal),
         dendrite: Uuid,
@@ -194,15 +652,113 @@ impl<T: Soma + 'static> Organelle<T> {
         let dendrite_sender = if let Some(sender) = self.somas.get(&dendrite) {
             sender.clone()
         } else {
-            bail!("unable to find dendrite")
+            bail!("unable to find dendrite {}", dendrite)
         };
 
+        let label = self.label(dendrite);
+
         self.handle.spawn(
             dendrite_sender
                 .send(Impulse::AddTerminal(terminal.0, synapse, terminal.1))
                 .map(|_| ())
-                .map_err(|_| {
-                    eprintln!("unable to add terminal");
+                .map_err(move |_| {
+                    error!(
+                        target: "organelle::soma",
+                        "unable to add terminal to {}",
+                        label
+                    );
+                }),
+        );
+
+        Ok(())
+    }
+
+    /// disconnect two somas that were connected with the specified synapse
+    ///
+    /// run consumes the organelle, so this can only be used to rewire it
+    /// before it starts.
+    pub fn disconnect(
+        &mut self,
+        dendrite: Uuid,
+        terminal: Uuid,
+        synapse: T::Synapse,
+    ) -> Result<()> {
+        let index = if let Some(index) = self.connections
+            .iter()
+            .position(|c| *c == (dendrite, terminal, synapse))
+        {
+            index
+        } else {
+            bail!(ErrorKind::InvalidSynapse(format!(
+                "{:?} does not connect {} to {}",
+                synapse, dendrite, terminal
+            )))
+        };
+
+        self.remove_terminal(terminal, dendrite, synapse)?;
+        self.remove_dendrite(dendrite, terminal, synapse)?;
+
+        self.connections.remove(index);
+
+        self.notify(TopologyEvent::Disconnected(dendrite, terminal, synapse));
+
+        Ok(())
+    }
+
+    fn remove_dendrite(
+        &self,
+        dendrite: Uuid,
+        terminal: Uuid,
+        synapse: T::Synapse,
+    ) -> Result<()> {
+        let terminal_sender = if let Some(sender) = self.somas.get(&terminal) {
+            sender.clone()
+        } else {
+            bail!("unable to find terminal {}", terminal)
+        };
+
+        let label = self.label(terminal);
+
+        self.handle.spawn(
+            terminal_sender
+                .send(Impulse::RemoveDendrite(dendrite, synapse))
+                .map(|_| ())
+                .map_err(move |_| {
+                    error!(
+                        target: "organelle::soma",
+                        "unable to remove dendrite from {}",
+                        label
+                    );
+                }),
+        );
+
+        Ok(())
+    }
+
+    fn remove_terminal(
+        &self,
+        terminal: Uuid,
+        dendrite: Uuid,
+        synapse: T::Synapse,
+    ) -> Result<()> {
+        let dendrite_sender = if let Some(sender) = self.somas.get(&dendrite) {
+            sender.clone()
+        } else {
+            bail!("unable to find dendrite {}", dendrite)
+        };
+
+        let label = self.label(dendrite);
+
+        self.handle.spawn(
+            dendrite_sender
+                .send(Impulse::RemoveTerminal(terminal, synapse))
+                .map(|_| ())
+                .map_err(move |_| {
+                    error!(
+                        target: "organelle::soma",
+                        "unable to remove terminal from {}",
+                        label
+                    );
                 }),
         );
 
@@ -226,6 +782,53 @@ impl<T: Soma + 'static> Organelle<T> {
         Ok(())
     }
 
+    fn stop_soma(
+        sender: mpsc::Sender<Impulse<T::Synapse>>,
+        stopped: Option<oneshot::Receiver<Result<()>>>,
+    ) -> Box<Future<Item = (), Error = Error>> {
+        // somas that have already stopped can no longer receive the impulse,
+        // so failed sends are ignored
+        let sent = sender
+            .send(Impulse::Stop)
+            .then(|_| future::ok::<(), Error>(()));
+
+        if let Some(stopped) = stopped {
+            // this resolves with the soma's answer to Stop once its task has
+            // ended, or is canceled if the task was dropped, which is just as
+            // final
+            Box::new(sent.and_then(|_| {
+                stopped.then(|result| match result {
+                    Ok(result) => result,
+                    Err(_) => Ok(()),
+                })
+            }))
+        } else {
+            Box::new(sent)
+        }
+    }
+
+    fn stop_all(&mut self) -> Box<Future<Item = (), Error = Error>> {
+        let mut nucleus = None;
+        let mut others = vec![];
+
+        for (uuid, sender) in &self.somas {
+            let stop =
+                Self::stop_soma(sender.clone(), self.stopped.remove(uuid));
+
+            if *uuid == self.main {
+                nucleus = Some(stop);
+            } else {
+                others.push(stop);
+            }
+        }
+
+        let nucleus = nucleus.unwrap();
+
+        // the nucleus is stopped last so it can finish up with the rest of
+        // the somas
+        Box::new(future::join_all(others).and_then(move |_| nucleus))
+    }
+
     #[async]
     fn perform_probe(
         self,
@@ -282,6 +885,7 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
             .collect();
 
         let uuid = self.uuid.unwrap();
+        let names = self.names.clone();
 
         Ok((
             self,
@@ -290,6 +894,7 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
                 somas: somas,
                 uuid: uuid,
                 name: unsafe { intrinsics::type_name::<Self>().into() },
+                names: names,
             },
         ))
     }
@@ -297,7 +902,19 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
     #[async(boxed)]
     fn update(mut self, imp: Impulse<T::Synapse>) -> Result<Self> {
         match imp {
-            Impulse::AddDendrite(_, _, _) | Impulse::AddTerminal(_, _, _) => {
+            Impulse::AddDendrite(_, _, _)
+            | Impulse::AddTerminal(_, _, _)
+            | Impulse::RemoveDendrite(_, _)
+            | Impulse::RemoveTerminal(_, _) => {
+                // the parent delivers these before Start, so anything later
+                // came from outside and would bypass connections
+                if self.uuid.is_some() {
+                    bail!(ErrorKind::InvalidSynapse(format!(
+                        "unable to rewire a running organelle with {:?}",
+                        imp
+                    )))
+                }
+
                 await!(
                     self.somas
                         .get(&self.nucleus())
@@ -309,9 +926,26 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
                 Ok(self)
             },
             Impulse::Start(uuid, tx, handle) => {
+                if self.acyclic {
+                    if let Some(cycle) = self.find_cycle() {
+                        let cycle: Vec<_> = cycle
+                            .into_iter()
+                            .map(|uuid| match self.name(uuid) {
+                                Some(name) => name.to_string(),
+                                None => uuid.to_string(),
+                            })
+                            .collect();
+
+                        bail!(ErrorKind::Cycle(cycle.join(" -> ")))
+                    }
+                }
+
                 self.uuid = Some(uuid);
 
-                let rx = mem::replace(&mut self.main_rx, None).unwrap();
+                let rx = match mem::replace(&mut self.main_rx, None) {
+                    Some(rx) => rx,
+                    None => bail!("organelle was started more than once"),
+                };
 
                 handle.spawn(
                     tx.clone()
@@ -329,7 +963,13 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
                 await!(self.perform_probe(settings, tx))
             },
 
-            Impulse::Stop | Impulse::Error(_) => unreachable!(),
+            Impulse::Stop => {
+                await!(self.stop_all())?;
+
+                Ok(self)
+            },
+
+            Impulse::Error(e) => bail!(e),
         }
     }
 
@@ -341,7 +981,7 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
     {
         let (tx, rx) = mpsc::channel(1);
 
-        let uuid = Uuid::new_v4();
+        let uuid = (self.uuid_generator)();
 
         await!(
             tx.clone()
@@ -349,19 +989,217 @@ impl<T: Soma + 'static> Soma for Organelle<T> {
                 .map_err(|_| Error::from("unable to send start signal"))
         )?;
 
-        #[async]
-        for imp in rx.map_err(|_| -> Error { unreachable!() }) {
+        let mut queue = rx.map_err(|_| -> Error { unreachable!() });
+
+        loop {
+            let (imp, rest) =
+                await!(queue.into_future()).map_err(|(e, _)| e)?;
+            queue = rest;
+
             match imp {
-                Impulse::Error(e) => bail!(e),
-                Impulse::Stop => break,
+                Some(Impulse::Error(e)) => bail!(e),
+                Some(Impulse::Stop) => {
+                    // somas may still send to the organelle while they stop,
+                    // so the queue is drained until they are all done
+                    let drain = queue.for_each(|imp| match imp {
+                        Impulse::Error(e) => Err(e),
+                        _ => Ok(()),
+                    });
 
-                _ => {
+                    match await!(self.stop_all().select2(drain)) {
+                        Ok(Either::A(_)) => (),
+                        Ok(Either::B((_, stopping))) => await!(stopping)?,
+                        Err(Either::A((e, _))) | Err(Either::B((e, _))) => {
+                            bail!(e)
+                        },
+                    }
+
+                    break;
+                },
+                Some(imp) => {
                     self = await!(self.update(imp))
                         .map_err(|e| -> Error { e.into() })?
                 },
+                None => break,
             }
         }
 
         Ok(())
     }
 }
+
+/// build an organelle by name instead of by uuid
+///
+/// errors in the wiring are held onto until build is called, so the builder
+/// can be chained without checking every step.
+pub struct OrganelleBuilder<T: Soma + 'static> {
+    organelle: Organelle<T>,
+    uuids: HashMap<String, Uuid>,
+    error: Option<Error>,
+}
+
+impl<T: Soma + 'static> OrganelleBuilder<T> {
+    /// start building an organelle around a named main soma
+    pub fn new(name: &str, main: T, handle: reactor::Handle) -> Self {
+        let mut organelle = Organelle::new(main, handle);
+        let mut uuids = HashMap::new();
+
+        let main = organelle.nucleus();
+
+        organelle.names.insert(main, name.to_string());
+        uuids.insert(name.to_string(), main);
+
+        Self {
+            organelle: organelle,
+            uuids: uuids,
+            error: None,
+        }
+    }
+
+    /// add a named soma to the organelle
+    pub fn add<U: Soma + 'static>(mut self, name: &str, soma: U) -> Self
+    where
+        U::Synapse: From<T::Synapse> + Into<T::Synapse>,
+        <U::Synapse as Synapse>::Dendrite: From<<T::Synapse as Synapse>::Dendrite>
+            + Into<<T::Synapse as Synapse>::Dendrite>,
+        <U::Synapse as Synapse>::Terminal: From<<T::Synapse as Synapse>::Terminal>
+            + Into<<T::Synapse as Synapse>::Terminal>,
+    {
+        if self.error.is_some() {
+            return self;
+        }
+
+        if self.uuids.contains_key(name) {
+            self.error = Some(format!("soma {} was added twice", name).into());
+        } else {
+            let uuid = self.organelle.add_soma_named(soma, name);
+
+            self.uuids.insert(name.to_string(), uuid);
+        }
+
+        self
+    }
+
+    /// connect two named somas together using the specified synapse
+    pub fn connect(
+        mut self,
+        dendrite: &str,
+        terminal: &str,
+        synapse: T::Synapse,
+    ) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+
+        let result = match (self.uuids.get(dendrite), self.uuids.get(terminal))
+        {
+            (Some(&dendrite), Some(&terminal)) => {
+                self.organelle.connect(dendrite, terminal, synapse)
+            },
+            (None, _) => Err(format!("no soma named {}", dendrite).into()),
+            (_, None) => Err(format!("no soma named {}", terminal).into()),
+        };
+
+        if let Err(e) = result {
+            self.error = Some(e);
+        }
+
+        self
+    }
+
+    /// finish building the organelle
+    ///
+    /// fails with the first error encountered while adding or connecting somas
+    pub fn build(self) -> Result<Organelle<T>> {
+        if let Some(e) = self.error {
+            Err(e)
+        } else {
+            Ok(self.organelle)
+        }
+    }
+}
+
+/// a reusable description of an organelle's somas and connections
+///
+/// somas are created by factories, so the same template can be instantiated
+/// any number of times. slot 0 is the main soma and every added soma takes
+/// the next slot.
+pub struct OrganelleTemplate<T: Soma + 'static> {
+    main: Box<Fn() -> T>,
+    somas: Vec<Box<Fn(&mut Organelle<T>) -> Uuid>>,
+    connections: Vec<(usize, usize, T::Synapse)>,
+}
+
+impl<T: Soma + 'static> OrganelleTemplate<T> {
+    /// create a new template using a factory for the main soma
+    pub fn new<F>(main: F) -> Self
+    where
+        F: Fn() -> T + 'static,
+    {
+        Self {
+            main: Box::new(main),
+            somas: vec![],
+            connections: vec![],
+        }
+    }
+
+    /// add a soma factory to the template and get its slot
+    pub fn add_soma<U, F>(&mut self, soma: F) -> usize
+    where
+        U: Soma + 'static,
+        F: Fn() -> U + 'static,
+        U::Synapse: From<T::Synapse> + Into<T::Synapse>,
+        <U::Synapse as Synapse>::Dendrite: From<<T::Synapse as Synapse>::Dendrite>
+            + Into<<T::Synapse as Synapse>::Dendrite>,
+        <U::Synapse as Synapse>::Terminal: From<<T::Synapse as Synapse>::Terminal>
+            + Into<<T::Synapse as Synapse>::Terminal>,
+    {
+        self.somas.push(Box::new(move |organelle: &mut Organelle<T>| {
+            organelle.add_soma(soma())
+        }));
+
+        self.somas.len()
+    }
+
+    /// connect the somas in two slots together using the specified synapse
+    pub fn connect(
+        &mut self,
+        dendrite: usize,
+        terminal: usize,
+        synapse: T::Synapse,
+    ) {
+        self.connections.push((dendrite, terminal, synapse));
+    }
+
+    /// create a fresh organelle with new somas wired like the template
+    ///
+    /// fails if a connection refers to a slot that does not exist or if the
+    /// organelle rejects one of the connections.
+    pub fn instantiate(&self, handle: reactor::Handle) -> Result<Organelle<T>> {
+        let mut organelle = Organelle::new((self.main)(), handle);
+
+        let mut uuids = vec![organelle.nucleus()];
+
+        for soma in &self.somas {
+            uuids.push(soma(&mut organelle));
+        }
+
+        for &(dendrite, terminal, synapse) in &self.connections {
+            match (uuids.get(dendrite), uuids.get(terminal)) {
+                (Some(&dendrite), Some(&terminal)) => {
+                    organelle.connect(dendrite, terminal, synapse)?
+                },
+                _ => bail!(
+                    "unable to connect slot {} to slot {} with {:?} - the \
+                     template has {} slots",
+                    dendrite,
+                    terminal,
+                    synapse,
+                    uuids.len()
+                ),
+            }
+        }
+
+        Ok(organelle)
+    }
+}
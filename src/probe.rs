@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use futures::prelude::*;
 use futures::unsync::{mpsc, oneshot};
 use tokio_core::reactor;
@@ -24,6 +26,15 @@ pub enum ConstraintData {
         soma: Uuid,
     },
 
+    /// at most one synapse of the given variant
+    #[serde(rename = "optional")]
+    Optional {
+        /// the enum variant for the synapse
+        variant: String,
+        /// the other soma involved in the synapse, if any
+        soma: Option<Uuid>,
+    },
+
     /// any number of synapses of the given variant
     #[serde(rename = "variadic")]
     Variadic {
@@ -49,6 +60,8 @@ pub enum SomaData {
         uuid: Uuid,
         /// name of the organelle
         name: String,
+        /// names given to somas with add_soma_named, by uuid
+        names: HashMap<Uuid, String>,
     },
 
     /// data associated with the axon of a soma
@@ -189,6 +202,7 @@ impl soma::Soma for Soma {
 
                 Ok(Self { dendrites: vec![] })
             },
+            Impulse::Stop => Ok(self),
 
             _ => bail!("unexpected impulse"),
         }
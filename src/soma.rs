@@ -42,6 +42,24 @@ pub enum Impulse<R: Synapse> {
     /// outputs. if your soma has outputs, it is best to wrap it with an Axon
     /// which can be used for validation purposes.
     AddTerminal(Uuid, R, R::Terminal),
+    /// remove the dendrite connecting the soma to another soma
+    ///
+    /// you should expect to handle this impulse if somas are disconnected
+    /// with Organelle::disconnect or Organelle::remove_soma. the soma should
+    /// drop the dendrite it received from the given soma with an AddDendrite.
+    ///
+    /// organelles cannot be rewired once they are running, so this impulse
+    /// always arrives before Start.
+    RemoveDendrite(Uuid, R),
+    /// remove the terminal connecting the soma to another soma
+    ///
+    /// you should expect to handle this impulse if somas are disconnected
+    /// with Organelle::disconnect or Organelle::remove_soma. the soma should
+    /// drop the terminal it received from the given soma with an AddTerminal.
+    ///
+    /// organelles cannot be rewired once they are running, so this impulse
+    /// always arrives before Start.
+    RemoveTerminal(Uuid, R),
     /// notify the soma that it has received all of its inputs and outputs
     ///
     /// you should always expect to handle this impulse because it will be
@@ -49,8 +67,10 @@ pub enum Impulse<R: Synapse> {
     Start(Uuid, mpsc::Sender<Impulse<R>>, reactor::Handle),
     /// stop the event loop and exit gracefully
     ///
-    /// you should not expect to handle this impulse at any time, it is handled
-    /// for you by the event loop
+    /// you should always expect to handle this impulse. each soma receives it
+    /// when the organelle stops, with the nucleus stopped last, so it can
+    /// finish up any work before returning. no impulses are delivered after
+    /// it.
     Stop,
     /// terminate the event loop with an error
     ///
@@ -82,6 +102,12 @@ where
             Impulse::AddTerminal(uuid, synapse, terminal) => {
                 Impulse::AddTerminal(uuid, synapse.into(), terminal.into())
             },
+            Impulse::RemoveDendrite(uuid, synapse) => {
+                Impulse::RemoveDendrite(uuid, synapse.into())
+            },
+            Impulse::RemoveTerminal(uuid, synapse) => {
+                Impulse::RemoveTerminal(uuid, synapse.into())
+            },
             Impulse::Stop => Impulse::Stop,
             Impulse::Error(e) => Impulse::Error(e),
 
@@ -94,6 +120,24 @@ where
     }
 }
 
+/// a step in a chain of middleware that processes impulses bound for a soma
+pub trait Middleware<S: Synapse> {
+    /// process an impulse, returning None to drop it
+    ///
+    /// Stop is delivered straight to the soma and never reaches middleware.
+    fn process(&mut self, imp: Impulse<S>) -> Option<Impulse<S>>;
+}
+
+impl<S, F> Middleware<S> for F
+where
+    S: Synapse,
+    F: FnMut(Impulse<S>) -> Option<Impulse<S>>,
+{
+    fn process(&mut self, imp: Impulse<S>) -> Option<Impulse<S>> {
+        self(imp)
+    }
+}
+
 /// a singular cell of functionality that can be ported between organelles
 ///
 /// you can think of a soma as a stream of impulses folded over a structure.
@@ -152,7 +196,10 @@ pub trait Soma: Sized {
         for imp in rx.map_err(|_| -> Error { unreachable!() }) {
             match imp {
                 Impulse::Error(e) => bail!(e),
-                Impulse::Stop => break,
+                Impulse::Stop => {
+                    await!(self.update(Impulse::Stop)).map_err(|e| e.into())?;
+                    break;
+                },
 
                 _ => self = await!(self.update(imp)).map_err(|e| e.into())?,
             }
@@ -1,6 +1,3 @@
-#[allow(dead_code)]
-mod dot;
-
 use std::collections::HashMap;
 use std::net::SocketAddr;
 
@@ -16,6 +13,7 @@ use uuid::Uuid;
 
 use super::{Error, Result};
 use axon::{Axon, Constraint};
+use dot;
 use organelle::Organelle;
 use probe::{self, ConstraintData, SomaData, Synapse, Terminal};
 use soma::{self, Impulse};
@@ -118,6 +116,7 @@ impl soma::Soma for Soma {
                     probe: None,
                 })
             },
+            Impulse::Stop => Ok(self),
 
             _ => bail!("unexpected impulse {:?}", imp),
         }
@@ -155,7 +154,11 @@ impl VisualizerTask {
 
         if self.open_on_start {
             if let Err(e) = open::that(format!("http://{}", addr.to_string())) {
-                eprintln!("unable to open default browser: {:#?}", e)
+                warn!(
+                    target: "organelle::visualizer",
+                    "unable to open default browser: {:#?}",
+                    e
+                )
             }
         }
 
@@ -167,7 +170,8 @@ impl VisualizerTask {
                 .for_each(move |connection| {
                     stream_handle.spawn(connection.map(|_| ()).or_else(
                         move |e| {
-                            eprintln!(
+                            error!(
+                                target: "organelle::visualizer",
                                 "error while serving HTTP request - {:?}",
                                 e
                             );
@@ -324,7 +328,11 @@ fn render_organelle(
 
                 for t in terminals {
                     match t {
-                        &ConstraintData::One { ref variant, soma } => {
+                        &ConstraintData::One { ref variant, soma }
+                        | &ConstraintData::Optional {
+                            ref variant,
+                            soma: Some(soma),
+                        } => {
                             let tgt_uuid = if let Some(uuid) = remap.get(&soma)
                             {
                                 *uuid
@@ -345,6 +353,7 @@ fn render_organelle(
                                     ))),
                                 ));
                         },
+                        &ConstraintData::Optional { soma: None, .. } => (),
                         &ConstraintData::Variadic {
                             ref variant,
                             ref somas,
@@ -396,7 +405,8 @@ fn render_axon(
     let terminals: Vec<String> = terminals
         .into_iter()
         .map(|t| match t {
-            ConstraintData::One { variant, .. } => {
+            ConstraintData::One { variant, .. }
+            | ConstraintData::Optional { variant, .. } => {
                 format!("<t_{}> {}", variant, variant)
             },
             ConstraintData::Variadic { variant, .. } => {
@@ -410,7 +420,8 @@ fn render_axon(
     let dendrites: Vec<String> = dendrites
         .into_iter()
         .map(|d| match d {
-            ConstraintData::One { variant, .. } => {
+            ConstraintData::One { variant, .. }
+            | ConstraintData::Optional { variant, .. } => {
                 format!("<d_{}> {}", variant, variant)
             },
             ConstraintData::Variadic { variant, .. } => {
@@ -452,6 +463,7 @@ fn render_soma(data: SomaData, remap: &HashMap<Uuid, Uuid>) -> dot::SubGraph {
             nucleus,
             somas,
             name,
+            ..
         } => render_organelle(uuid, name, *nucleus, somas, remap),
         SomaData::Axon {
             terminals,
@@ -6,6 +6,7 @@ extern crate error_chain;
 extern crate futures_await as futures;
 extern crate organelle;
 extern crate tokio_core;
+extern crate uuid;
 
 use futures::prelude::*;
 use futures::unsync;
@@ -78,6 +79,7 @@ impl Soma for GiverSoma {
 
                 Ok(Self { tx: None })
             },
+            Impulse::Stop => Ok(self),
             _ => bail!("unexpected impulse"),
         }
     }
@@ -109,6 +111,9 @@ impl Soma for TakerSoma {
                 Synapse::GiveSomething,
                 Dendrite::Taker(rx),
             ) => Ok(Self { rx: Some(rx) }),
+            Impulse::RemoveDendrite(_, Synapse::GiveSomething) => {
+                Ok(Self { rx: None })
+            },
             Impulse::Start(_, tx, _) => {
                 await!(
                     self.rx
@@ -122,6 +127,7 @@ impl Soma for TakerSoma {
 
                 Ok(Self { rx: None })
             },
+            Impulse::Stop => Ok(self),
             _ => bail!("unexpected impulse"),
         }
     }
@@ -197,3 +203,540 @@ fn test_require_one() {
         }
     }
 }
+
+#[test]
+fn test_synapse_predicates() {
+    let one = TakerSoma::axon();
+
+    assert!(one.synapse_is_required(Synapse::GiveSomething));
+    assert!(!one.synapse_is_variadic(Synapse::GiveSomething));
+
+    let variadic = Axon::new(
+        TakerSoma { rx: None },
+        vec![],
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+    );
+
+    assert!(!variadic.synapse_is_required(Synapse::GiveSomething));
+    assert!(variadic.synapse_is_variadic(Synapse::GiveSomething));
+
+    let unknown = Axon::new(TakerSoma { rx: None }, vec![], vec![]);
+
+    assert!(!unknown.synapse_is_required(Synapse::GiveSomething));
+    assert!(!unknown.synapse_is_variadic(Synapse::GiveSomething));
+}
+
+#[test]
+fn test_connected_at() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    let first = uuid::Uuid::new_v4();
+    let second = uuid::Uuid::new_v4();
+
+    let (_tx1, rx1) = organelle::Synapse::synapse(Synapse::GiveSomething);
+    let (_tx2, rx2) = organelle::Synapse::synapse(Synapse::GiveSomething);
+
+    let axon = axon.update(Impulse::AddDendrite(
+        first,
+        Synapse::GiveSomething,
+        rx1,
+    )).wait()
+        .unwrap();
+    let axon = axon.update(Impulse::AddDendrite(
+        second,
+        Synapse::GiveSomething,
+        rx2,
+    )).wait()
+        .unwrap();
+
+    let first_at = axon.dendrite_connected_at(Synapse::GiveSomething, first)
+        .unwrap();
+    let second_at = axon.dendrite_connected_at(Synapse::GiveSomething, second)
+        .unwrap();
+
+    assert!(first_at <= second_at);
+    assert_eq!(
+        axon.dendrite_connected_at(
+            Synapse::GiveSomething,
+            uuid::Uuid::new_v4()
+        ),
+        None
+    );
+    assert_eq!(
+        axon.terminal_connected_at(Synapse::GiveSomething, first),
+        None
+    );
+}
+
+#[test]
+fn test_remove_dendrite() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    let giver = uuid::Uuid::new_v4();
+
+    let (_tx, rx) = organelle::Synapse::synapse(Synapse::GiveSomething);
+
+    let axon = axon.update(Impulse::AddDendrite(
+        giver,
+        Synapse::GiveSomething,
+        rx,
+    )).wait()
+        .unwrap();
+    let axon = axon.update(Impulse::RemoveDendrite(
+        giver,
+        Synapse::GiveSomething,
+    )).wait()
+        .unwrap();
+
+    assert_eq!(axon.dendrite_connected_at(Synapse::GiveSomething, giver), None);
+
+    // removing it a second time is invalid
+    if let Err(e) = axon.update(Impulse::RemoveDendrite(
+        giver,
+        Synapse::GiveSomething,
+    )).wait()
+    {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("the dendrite was already removed")
+    }
+}
+
+fn add_taker_dendrite(axon: Axon<TakerSoma>) -> Result<Axon<TakerSoma>> {
+    let (_, rx) = organelle::Synapse::synapse(Synapse::GiveSomething);
+
+    axon.update(Impulse::AddDendrite(
+        uuid::Uuid::new_v4(),
+        Synapse::GiveSomething,
+        rx,
+    )).wait()
+}
+
+#[test]
+fn test_require_n() {
+    let core = reactor::Core::new().unwrap();
+    let (tx, _rx) = unsync::mpsc::channel(1);
+
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::RequireN(Synapse::GiveSomething, 2)],
+        vec![],
+    );
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    if let Err(e) = axon.update(Impulse::Start(
+        uuid::Uuid::new_v4(),
+        tx,
+        core.handle(),
+    )).wait()
+    {
+        match e.kind() {
+            &ErrorKind::MissingSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("TakerSoma has only one of two inputs, so it should fail")
+    }
+}
+
+#[test]
+fn test_range() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Range(Synapse::GiveSomething, 0, 1)],
+        vec![],
+    );
+
+    assert!(!axon.synapse_is_required(Synapse::GiveSomething));
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    if let Err(e) = add_taker_dendrite(axon) {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("TakerSoma accepts at most one input, so it should fail")
+    }
+
+    let core = reactor::Core::new().unwrap();
+    let (tx, _rx) = unsync::mpsc::channel(1);
+
+    // a range that can never be met is reported before any connections
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Range(Synapse::GiveSomething, 3, 1)],
+        vec![],
+    );
+
+    assert_eq!(
+        axon.constraints_satisfied(),
+        Err(vec![Synapse::GiveSomething])
+    );
+
+    if let Err(e) = axon.update(Impulse::Start(
+        uuid::Uuid::new_v4(),
+        tx,
+        core.handle(),
+    )).wait()
+    {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("a range with its minimum above its maximum should fail")
+    }
+}
+
+struct LoneSoma;
+
+impl Soma for LoneSoma {
+    type Synapse = Synapse;
+    type Error = Error;
+
+    #[async(boxed)]
+    fn update(self, imp: Impulse<Self::Synapse>) -> Result<Self> {
+        match imp {
+            Impulse::Start(_, _, _) => Ok(self),
+            _ => bail!("unexpected impulse"),
+        }
+    }
+}
+
+#[test]
+fn test_optional() {
+    let core = reactor::Core::new().unwrap();
+    let (tx, _rx) = unsync::mpsc::channel(1);
+
+    // an optional synapse can be left unconnected
+    let axon = Axon::new(
+        LoneSoma,
+        vec![Constraint::Optional(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    assert!(!axon.synapse_is_required(Synapse::GiveSomething));
+    assert!(!axon.synapse_is_variadic(Synapse::GiveSomething));
+
+    axon.update(Impulse::Start(uuid::Uuid::new_v4(), tx, core.handle()))
+        .wait()
+        .unwrap();
+
+    // but it still accepts only one
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Optional(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    if let Err(e) = add_taker_dendrite(axon) {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("TakerSoma accepts at most one input, so it should fail")
+    }
+}
+
+#[test]
+fn test_connection_iterators() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    assert_eq!(
+        axon.dendrite_synapses().collect::<Vec<_>>(),
+        vec![Synapse::GiveSomething]
+    );
+    assert_eq!(axon.terminal_synapses().count(), 0);
+    assert_eq!(axon.dendrites().count(), 0);
+
+    let axon = add_taker_dendrite(add_taker_dendrite(axon).unwrap()).unwrap();
+
+    let dendrites: Vec<_> = axon.dendrites().collect();
+
+    assert_eq!(dendrites.len(), 2);
+    assert!(
+        dendrites
+            .iter()
+            .all(|&(synapse, _)| synapse == Synapse::GiveSomething)
+    );
+    assert_eq!(axon.terminals().count(), 0);
+}
+
+#[test]
+fn test_connection_counts() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::One(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    assert_eq!(axon.dendrite_count(Synapse::GiveSomething).unwrap(), 0);
+    assert!(axon.terminal_count(Synapse::GiveSomething).is_err());
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    assert_eq!(axon.dendrite_count(Synapse::GiveSomething).unwrap(), 1);
+
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    let axon = add_taker_dendrite(add_taker_dendrite(axon).unwrap()).unwrap();
+
+    assert_eq!(axon.dendrite_count(Synapse::GiveSomething).unwrap(), 2);
+}
+
+#[test]
+fn test_duplicate_synapse() {
+    let core = reactor::Core::new().unwrap();
+    let (tx, _rx) = unsync::mpsc::channel(1);
+
+    let axon = Axon::new(
+        LoneSoma,
+        vec![
+            Constraint::Optional(Synapse::GiveSomething),
+            Constraint::Variadic(Synapse::GiveSomething),
+        ],
+        vec![],
+    );
+
+    if let Err(e) = axon.update(Impulse::Start(
+        uuid::Uuid::new_v4(),
+        tx,
+        core.handle(),
+    )).wait()
+    {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                assert!(msg.contains("GiveSomething"));
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("LoneSoma specifies GiveSomething twice, so it should fail")
+    }
+}
+
+#[test]
+fn test_new_strict() {
+    if let Err(e) = Axon::new_strict(LoneSoma, vec![], vec![]) {
+        match e.kind() {
+            &ErrorKind::MissingSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("an axon without constraints should be rejected")
+    }
+
+    Axon::new_strict(
+        LoneSoma,
+        vec![Constraint::Optional(Synapse::GiveSomething)],
+        vec![],
+    ).unwrap();
+
+    if let Err(e) = Axon::new_strict(
+        LoneSoma,
+        vec![],
+        vec![Constraint::Range(Synapse::GiveSomething, 3, 1)],
+    ) {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("a range with its minimum above its maximum should be rejected")
+    }
+}
+
+#[test]
+fn test_inner_soma() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::One(Synapse::GiveSomething)],
+        vec![],
+    );
+
+    assert!(axon.soma().rx.is_none());
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    assert!(axon.soma().rx.is_some());
+    assert!(axon.into_soma().rx.is_some());
+}
+
+#[test]
+fn test_constraints_satisfied() {
+    let axon = Axon::new(
+        TakerSoma { rx: None },
+        vec![Constraint::RequireN(Synapse::GiveSomething, 2)],
+        vec![],
+    );
+
+    assert_eq!(
+        axon.constraints_satisfied(),
+        Err(vec![Synapse::GiveSomething])
+    );
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    assert_eq!(
+        axon.constraints_satisfied(),
+        Err(vec![Synapse::GiveSomething])
+    );
+
+    let axon = add_taker_dendrite(axon).unwrap();
+
+    assert_eq!(axon.constraints_satisfied(), Ok(()));
+
+    // start rejects duplicate synapses even when they are connected
+    let axon = Axon::new(
+        LoneSoma,
+        vec![
+            Constraint::Optional(Synapse::GiveSomething),
+            Constraint::Variadic(Synapse::GiveSomething),
+        ],
+        vec![],
+    );
+
+    assert_eq!(
+        axon.constraints_satisfied(),
+        Err(vec![Synapse::GiveSomething])
+    );
+}
+
+struct RelaySoma;
+
+impl Soma for RelaySoma {
+    type Synapse = Synapse;
+    type Error = Error;
+
+    #[async(boxed)]
+    fn update(self, imp: Impulse<Self::Synapse>) -> Result<Self> {
+        match imp {
+            Impulse::AddDendrite(_, _, _)
+            | Impulse::AddTerminal(_, _, _)
+            | Impulse::RemoveDendrite(_, _)
+            | Impulse::RemoveTerminal(_, _) => Ok(self),
+            _ => bail!("unexpected impulse"),
+        }
+    }
+}
+
+#[test]
+fn test_connected_at_bidirectional() {
+    let axon = Axon::new(
+        RelaySoma,
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+        vec![Constraint::Variadic(Synapse::GiveSomething)],
+    );
+
+    let peer = uuid::Uuid::new_v4();
+
+    let (tx, rx) = organelle::Synapse::synapse(Synapse::GiveSomething);
+
+    let axon = axon.update(Impulse::AddDendrite(
+        peer,
+        Synapse::GiveSomething,
+        rx,
+    )).wait()
+        .unwrap();
+    let axon = axon.update(Impulse::AddTerminal(
+        peer,
+        Synapse::GiveSomething,
+        tx,
+    )).wait()
+        .unwrap();
+
+    let dendrite_at = axon.dendrite_connected_at(Synapse::GiveSomething, peer)
+        .unwrap();
+
+    let axon = axon.update(Impulse::RemoveTerminal(
+        peer,
+        Synapse::GiveSomething,
+    )).wait()
+        .unwrap();
+
+    // the dendrite from the same soma is still connected
+    assert_eq!(
+        axon.dendrite_connected_at(Synapse::GiveSomething, peer),
+        Some(dendrite_at)
+    );
+    assert_eq!(
+        axon.terminal_connected_at(Synapse::GiveSomething, peer),
+        None
+    );
+}
+
+#[test]
+fn test_lose_required_synapse() {
+    let core = reactor::Core::new().unwrap();
+    let (tx, _rx) = unsync::mpsc::channel(1);
+
+    let giver = uuid::Uuid::new_v4();
+    let (_, rx) = organelle::Synapse::synapse(Synapse::GiveSomething);
+
+    let axon = TakerSoma::axon()
+        .update(Impulse::AddDendrite(giver, Synapse::GiveSomething, rx))
+        .wait()
+        .unwrap();
+
+    let axon = axon.update(Impulse::Start(
+        uuid::Uuid::new_v4(),
+        tx,
+        core.handle(),
+    )).wait()
+        .unwrap();
+
+    if let Err(e) = axon.update(Impulse::RemoveDendrite(
+        giver,
+        Synapse::GiveSomething,
+    )).wait()
+    {
+        match e.kind() {
+            &ErrorKind::MissingSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("TakerSoma requires its input once started")
+    }
+}
@@ -198,6 +198,7 @@ impl Soma for Incrementer {
 
                 Ok(self)
             },
+            Impulse::Stop => Ok(self),
 
             _ => bail!("unexpected impulse"),
         }
@@ -270,6 +271,7 @@ impl Soma for Counter {
 
                 Ok(self)
             },
+            Impulse::Stop => Ok(self),
 
             _ => bail!("unexpected impulse"),
         }
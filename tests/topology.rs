@@ -0,0 +1,860 @@
+#![feature(proc_macro, conservative_impl_trait, generators)]
+
+#[macro_use]
+extern crate error_chain;
+
+extern crate futures_await as futures;
+extern crate organelle;
+extern crate tokio_core;
+extern crate uuid;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use futures::prelude::*;
+use futures::future;
+use futures::unsync;
+use organelle::*;
+use tokio_core::reactor;
+
+#[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+enum Synapse {
+    Link,
+    Feedback,
+}
+
+#[derive(Debug)]
+enum Terminal {
+    Link(unsync::mpsc::Sender<()>),
+}
+
+#[derive(Debug)]
+enum Dendrite {
+    Link(unsync::mpsc::Receiver<()>),
+}
+
+impl organelle::Synapse for Synapse {
+    type Terminal = Terminal;
+    type Dendrite = Dendrite;
+
+    fn synapse(self) -> (Self::Terminal, Self::Dendrite) {
+        match self {
+            Synapse::Link | Synapse::Feedback => {
+                let (tx, rx) = unsync::mpsc::channel(1);
+
+                (Terminal::Link(tx), Dendrite::Link(rx))
+            },
+        }
+    }
+}
+
+struct IdleSoma;
+
+impl Soma for IdleSoma {
+    type Synapse = Synapse;
+    type Error = Error;
+
+    #[async(boxed)]
+    fn update(self, imp: Impulse<Self::Synapse>) -> Result<Self> {
+        match imp {
+            Impulse::AddDendrite(_, _, _)
+            | Impulse::AddTerminal(_, _, _)
+            | Impulse::RemoveDendrite(_, _)
+            | Impulse::RemoveTerminal(_, _)
+            | Impulse::Start(_, _, _)
+            | Impulse::Stop => Ok(self),
+
+            _ => bail!("unexpected impulse"),
+        }
+    }
+}
+
+struct FailSoma;
+
+impl Soma for FailSoma {
+    type Synapse = Synapse;
+    type Error = Error;
+
+    #[async(boxed)]
+    fn update(self, imp: Impulse<Self::Synapse>) -> Result<Self> {
+        match imp {
+            Impulse::Stop => Ok(self),
+            _ => bail!("impulse should have been dropped"),
+        }
+    }
+}
+
+#[test]
+fn test_observe_topology() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let events = Rc::new(RefCell::new(vec![]));
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    {
+        let events = events.clone();
+        organelle.observe_topology(move |e| events.borrow_mut().push(e));
+    }
+
+    let nucleus = organelle.nucleus();
+    let a = organelle.add_soma(IdleSoma);
+    let b = organelle.add_soma(IdleSoma);
+
+    organelle.connect(nucleus, a, Synapse::Link).unwrap();
+    organelle.connect(a, b, Synapse::Link).unwrap();
+
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            TopologyEvent::SomaAdded(a),
+            TopologyEvent::SomaAdded(b),
+            TopologyEvent::Connected(nucleus, a, Synapse::Link),
+            TopologyEvent::Connected(a, b, Synapse::Link),
+        ]
+    );
+}
+
+#[test]
+fn test_uuids() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let a = organelle.add_soma(IdleSoma);
+    let b = organelle.add_soma(IdleSoma);
+
+    let mut uuids: Vec<_> = organelle.uuids().collect();
+    let mut expected = vec![nucleus, a, b];
+
+    uuids.sort();
+    expected.sort();
+
+    assert_eq!(uuids, expected);
+}
+
+struct StopOnStart;
+
+impl Middleware<Synapse> for StopOnStart {
+    fn process(&mut self, imp: Impulse<Synapse>) -> Option<Impulse<Synapse>> {
+        if let Impulse::Start(_, ref tx, ref handle) = imp {
+            handle.spawn(
+                tx.clone()
+                    .send(Impulse::Stop)
+                    .map(|_| ())
+                    .map_err(|_| ()),
+            );
+        }
+
+        Some(imp)
+    }
+}
+
+struct DropStarts;
+
+impl Middleware<Synapse> for DropStarts {
+    fn process(&mut self, imp: Impulse<Synapse>) -> Option<Impulse<Synapse>> {
+        match imp {
+            Impulse::Start(_, _, _) => None,
+            _ => Some(imp),
+        }
+    }
+}
+
+#[test]
+fn test_middleware() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle.clone());
+
+    // the first step stops the organelle once it sees start, and the second
+    // keeps start from ever reaching the failing soma
+    organelle.add_soma_with_middleware(
+        FailSoma,
+        vec![Box::new(StopOnStart), Box::new(DropStarts)],
+    );
+
+    core.run(organelle.run(handle)).unwrap();
+}
+
+#[test]
+fn test_closure_middleware() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle.clone());
+
+    let drop_starts = |imp: Impulse<Synapse>| match imp {
+        Impulse::Start(_, _, _) => None,
+        imp => Some(imp),
+    };
+
+    organelle.add_soma_with_middleware(
+        FailSoma,
+        vec![Box::new(StopOnStart), Box::new(drop_starts)],
+    );
+
+    core.run(organelle.run(handle)).unwrap();
+}
+
+#[test]
+fn test_connect_unknown_soma() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let unknown = uuid::Uuid::new_v4();
+
+    for &(dendrite, terminal) in &[(nucleus, unknown), (unknown, nucleus)] {
+        let e = organelle
+            .connect(dendrite, terminal, Synapse::Link)
+            .unwrap_err()
+            .to_string();
+
+        assert!(e.contains(&nucleus.to_string()));
+        assert!(e.contains(&unknown.to_string()));
+    }
+}
+
+#[test]
+fn test_connect_duplicate() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+
+    if let Err(e) = organelle.connect(nucleus, soma, Synapse::Link) {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("duplicate synapse should be rejected")
+    }
+
+    // the reverse direction is a different connection
+    organelle.connect(soma, nucleus, Synapse::Link).unwrap();
+}
+
+#[test]
+fn test_disconnect() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let events = Rc::new(RefCell::new(vec![]));
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    {
+        let events = events.clone();
+        organelle.observe_topology(move |e| events.borrow_mut().push(e));
+    }
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    assert!(organelle.disconnect(nucleus, soma, Synapse::Link).is_err());
+
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+    organelle.disconnect(nucleus, soma, Synapse::Link).unwrap();
+
+    assert!(organelle.disconnect(nucleus, soma, Synapse::Link).is_err());
+
+    // the somas can be connected again once they are disconnected
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            TopologyEvent::SomaAdded(soma),
+            TopologyEvent::Connected(nucleus, soma, Synapse::Link),
+            TopologyEvent::Disconnected(nucleus, soma, Synapse::Link),
+            TopologyEvent::Connected(nucleus, soma, Synapse::Link),
+        ]
+    );
+}
+
+#[test]
+fn test_remove_soma() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let events = Rc::new(RefCell::new(vec![]));
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+
+    {
+        let events = events.clone();
+        organelle.observe_topology(move |e| events.borrow_mut().push(e));
+    }
+
+    assert!(organelle.remove_soma(nucleus).is_err());
+    assert!(organelle.remove_soma(uuid::Uuid::new_v4()).is_err());
+
+    organelle.remove_soma(soma).unwrap();
+
+    assert_eq!(organelle.uuids().collect::<Vec<_>>(), vec![nucleus]);
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            TopologyEvent::Disconnected(nucleus, soma, Synapse::Link),
+            TopologyEvent::SomaRemoved(soma),
+        ]
+    );
+
+    // the connection went away with the soma
+    assert!(organelle.disconnect(nucleus, soma, Synapse::Link).is_err());
+}
+
+#[test]
+fn test_to_dot() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+
+    let dot = organelle.to_dot();
+
+    assert!(dot.starts_with("digraph"));
+    assert!(dot.contains(&format!("\"{}\" [shape=doubleoctagon", nucleus)));
+    assert!(dot.contains(&format!("\"{}\" -> \"{}\"", nucleus, soma)));
+    assert!(dot.contains("label=\"Link\""));
+}
+
+#[test]
+fn test_with_settings() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::with_settings(
+        IdleSoma,
+        OrganelleSettings::default()
+            .soma_buffer(1024)
+            .queue_buffer(1024),
+        handle.clone(),
+    );
+
+    organelle.add_soma_with_middleware(IdleSoma, vec![Box::new(StopOnStart)]);
+
+    let mut sender = organelle.impulse_sender();
+
+    // nothing is read from the queue until the organelle starts, so each of
+    // these has to fit in its buffer
+    core.run(future::lazy(move || {
+        for _ in 0..200 {
+            match sender.start_send(Impulse::Stop) {
+                Ok(AsyncSink::Ready) => (),
+                _ => panic!("the queue should hold more than the default"),
+            }
+        }
+
+        Ok::<(), ()>(())
+    })).unwrap();
+
+    core.run(organelle.run(handle)).unwrap();
+}
+
+#[test]
+fn test_names() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let parser = organelle.add_soma_named(IdleSoma, "parser_stage");
+
+    assert_eq!(organelle.name(nucleus), None);
+    assert_eq!(organelle.name(parser), Some("parser_stage"));
+
+    assert!(
+        organelle
+            .to_dot()
+            .contains(&format!("label=\"parser_stage ({})\"", parser))
+    );
+
+    organelle.remove_soma(parser).unwrap();
+
+    assert_eq!(organelle.name(parser), None);
+}
+
+#[test]
+fn test_builder() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let organelle = OrganelleBuilder::new("main", IdleSoma, handle.clone())
+        .add("a", IdleSoma)
+        .add("b", IdleSoma)
+        .connect("main", "a", Synapse::Link)
+        .connect("a", "b", Synapse::Link)
+        .build()
+        .unwrap();
+
+    assert_eq!(organelle.name(organelle.nucleus()), Some("main"));
+    assert_eq!(organelle.uuids().count(), 3);
+
+    let result = OrganelleBuilder::new("main", IdleSoma, handle.clone())
+        .add("a", IdleSoma)
+        .connect("main", "missing", Synapse::Link)
+        .add("b", IdleSoma)
+        .build();
+
+    assert!(result.unwrap_err().to_string().contains("missing"));
+
+    let result = OrganelleBuilder::new("main", IdleSoma, handle)
+        .add("a", IdleSoma)
+        .add("a", IdleSoma)
+        .build();
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_connect_bidirectional() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let events = Rc::new(RefCell::new(vec![]));
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    {
+        let events = events.clone();
+        organelle.observe_topology(move |e| events.borrow_mut().push(e));
+    }
+
+    organelle
+        .connect_bidirectional(nucleus, soma, Synapse::Link)
+        .unwrap();
+
+    assert_eq!(
+        *events.borrow(),
+        vec![
+            TopologyEvent::Connected(nucleus, soma, Synapse::Link),
+            TopologyEvent::Connected(soma, nucleus, Synapse::Link),
+        ]
+    );
+}
+
+#[test]
+fn test_require_acyclic() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = OrganelleBuilder::new("main", IdleSoma, handle.clone())
+        .add("a", IdleSoma)
+        .add("b", IdleSoma)
+        .connect("main", "a", Synapse::Link)
+        .connect("a", "b", Synapse::Link)
+        .connect("b", "a", Synapse::Link)
+        .build()
+        .unwrap();
+
+    organelle.require_acyclic();
+
+    if let Err(e) = core.run(organelle.run(handle.clone())) {
+        match e.kind() {
+            &ErrorKind::Cycle(ref msg) => {
+                assert!(msg.contains("a -> b") || msg.contains("b -> a"));
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("a and b form a cycle, so the organelle should not start")
+    }
+
+    let mut organelle = Organelle::new(IdleSoma, handle.clone());
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle
+        .add_soma_with_middleware(IdleSoma, vec![Box::new(StopOnStart)]);
+
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+    organelle.require_acyclic();
+
+    core.run(organelle.run(handle.clone())).unwrap();
+
+    // a bidirectional connection is always a cycle
+    let mut organelle = Organelle::new(IdleSoma, handle.clone());
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    organelle
+        .connect_bidirectional(nucleus, soma, Synapse::Link)
+        .unwrap();
+    organelle.require_acyclic();
+
+    if let Err(e) = core.run(organelle.run(handle)) {
+        match e.kind() {
+            &ErrorKind::Cycle(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("a bidirectional connection should be rejected as a cycle")
+    }
+}
+
+#[test]
+fn test_somas_with_synapse() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let a = organelle.add_soma(IdleSoma);
+    let b = organelle.add_soma(IdleSoma);
+
+    assert!(organelle.somas_with_terminal(Synapse::Link).is_empty());
+
+    organelle.connect(nucleus, a, Synapse::Link).unwrap();
+    organelle.connect(nucleus, b, Synapse::Link).unwrap();
+
+    // the first soma passed to connect receives the terminal
+    assert_eq!(organelle.somas_with_terminal(Synapse::Link), vec![nucleus]);
+    assert_eq!(organelle.somas_with_dendrite(Synapse::Link), vec![a, b]);
+}
+
+fn sequential_uuids() -> impl FnMut() -> uuid::Uuid {
+    let mut next = 0;
+
+    move || {
+        let mut bytes = [0; 16];
+
+        next += 1;
+        bytes[15] = next;
+
+        uuid::Uuid::from_bytes(&bytes).unwrap()
+    }
+}
+
+#[test]
+fn test_uuid_generator() {
+    let core = reactor::Core::new().unwrap();
+
+    let dots: Vec<_> = (0..2)
+        .map(|_| {
+            let mut organelle = Organelle::with_uuid_generator(
+                IdleSoma,
+                OrganelleSettings::default(),
+                core.handle(),
+                sequential_uuids(),
+            );
+
+            let nucleus = organelle.nucleus();
+            let soma = organelle.add_soma(IdleSoma);
+
+            organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+
+            organelle.to_dot()
+        })
+        .collect();
+
+    assert_eq!(dots[0], dots[1]);
+    assert!(dots[0].contains("00000000-0000-0000-0000-000000000001"));
+}
+
+#[test]
+fn test_connect_multiple_synapses() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle);
+
+    let nucleus = organelle.nucleus();
+    let soma = organelle.add_soma(IdleSoma);
+
+    organelle.connect(nucleus, soma, Synapse::Link).unwrap();
+    organelle.connect(nucleus, soma, Synapse::Feedback).unwrap();
+
+    assert_eq!(organelle.somas_with_dendrite(Synapse::Link), vec![soma]);
+    assert_eq!(organelle.somas_with_dendrite(Synapse::Feedback), vec![soma]);
+
+    organelle.disconnect(nucleus, soma, Synapse::Link).unwrap();
+
+    // the other synapse between the pair is untouched
+    assert!(organelle.somas_with_dendrite(Synapse::Link).is_empty());
+    assert_eq!(organelle.somas_with_dendrite(Synapse::Feedback), vec![soma]);
+}
+
+#[test]
+fn test_impulse_sender() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let organelle = Organelle::new(IdleSoma, handle.clone());
+
+    handle.spawn(
+        organelle
+            .impulse_sender()
+            .send(Impulse::Stop)
+            .map(|_| ())
+            .map_err(|_| ()),
+    );
+
+    core.run(organelle.run(handle)).unwrap();
+}
+
+#[test]
+fn test_template() {
+    let core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut template = OrganelleTemplate::new(|| IdleSoma);
+
+    let a = template.add_soma(|| IdleSoma);
+    let b = template.add_soma(|| IdleSoma);
+
+    template.connect(0, a, Synapse::Link);
+    template.connect(a, b, Synapse::Link);
+
+    let first = template.instantiate(handle.clone()).unwrap();
+    let second = template.instantiate(handle.clone()).unwrap();
+
+    assert_eq!(first.uuids().count(), 3);
+    assert_eq!(second.uuids().count(), 3);
+
+    // each instance gets its own somas
+    assert_ne!(first.nucleus(), second.nucleus());
+    assert_eq!(
+        second.somas_with_terminal(Synapse::Link).len(),
+        first.somas_with_terminal(Synapse::Link).len()
+    );
+
+    template.connect(b, 3, Synapse::Link);
+
+    assert!(template.instantiate(handle).is_err());
+}
+
+struct RecordStop {
+    name: &'static str,
+    stops: Rc<RefCell<Vec<&'static str>>>,
+}
+
+impl Soma for RecordStop {
+    type Synapse = Synapse;
+    type Error = Error;
+
+    #[async(boxed)]
+    fn update(self, imp: Impulse<Self::Synapse>) -> Result<Self> {
+        match imp {
+            Impulse::Start(_, _, _) => Ok(self),
+            Impulse::Stop => {
+                self.stops.borrow_mut().push(self.name);
+
+                Ok(self)
+            },
+
+            _ => bail!("unexpected impulse"),
+        }
+    }
+}
+
+#[test]
+fn test_stop() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let stops = Rc::new(RefCell::new(vec![]));
+    let record = |name: &'static str| RecordStop {
+        name: name,
+        stops: stops.clone(),
+    };
+
+    let mut inner = Organelle::new(record("inner nucleus"), handle.clone());
+    inner.add_soma(record("inner soma"));
+
+    let mut outer = Organelle::new(record("outer nucleus"), handle.clone());
+    outer.add_soma(inner);
+    outer.add_soma_with_middleware(IdleSoma, vec![Box::new(StopOnStart)]);
+
+    core.run(outer.run(handle)).unwrap();
+
+    let stops = stops.borrow();
+    let position = |name| stops.iter().position(|stop| *stop == name);
+
+    // every soma sees stop before run resolves, including those nested in
+    // another organelle, and each nucleus is stopped after its somas
+    assert_eq!(stops.len(), 4);
+    assert!(position("inner soma") < position("inner nucleus"));
+    assert_eq!(position("outer nucleus"), Some(3));
+}
+
+#[test]
+fn test_error_impulse() {
+    let core = reactor::Core::new().unwrap();
+
+    let organelle = Organelle::new(IdleSoma, core.handle());
+
+    let error = ErrorKind::MissingSynapse("lost a synapse".into()).into();
+
+    // the error is passed along with its kind intact
+    if let Err(e) = organelle.update(Impulse::Error(error)).wait() {
+        match e.kind() {
+            &ErrorKind::MissingSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("an error impulse should fail the organelle")
+    }
+}
+
+#[test]
+fn test_second_start() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let organelle = Organelle::new(IdleSoma, handle.clone());
+
+    let (tx, _rx) = unsync::mpsc::channel(1);
+
+    handle.spawn(
+        organelle
+            .impulse_sender()
+            .send(Impulse::Start(uuid::Uuid::new_v4(), tx, handle.clone()))
+            .map(|_| ())
+            .map_err(|_| ()),
+    );
+
+    if let Err(e) = core.run(organelle.run(handle)) {
+        println!("got expected error: {}", e)
+    } else {
+        panic!("a stray start impulse should fail the organelle")
+    }
+}
+
+#[test]
+#[should_panic(expected = "more than once")]
+fn test_uuid_generator_repeats() {
+    let core = reactor::Core::new().unwrap();
+
+    let mut organelle = Organelle::with_uuid_generator(
+        IdleSoma,
+        OrganelleSettings::default(),
+        core.handle(),
+        || uuid::Uuid::nil(),
+    );
+
+    organelle.add_soma(IdleSoma);
+}
+
+struct FailOnStop;
+
+impl Soma for FailOnStop {
+    type Synapse = Synapse;
+    type Error = Error;
+
+    #[async(boxed)]
+    fn update(self, imp: Impulse<Self::Synapse>) -> Result<Self> {
+        match imp {
+            Impulse::Start(_, _, _) => Ok(self),
+            Impulse::Stop => bail!("unable to stop"),
+
+            _ => bail!("unexpected impulse"),
+        }
+    }
+}
+
+#[test]
+fn test_stop_error() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(FailOnStop, handle.clone());
+    organelle.add_soma_with_middleware(IdleSoma, vec![Box::new(StopOnStart)]);
+
+    // the nucleus is stopped last, so its error is the last thing to arrive
+    if let Err(e) = core.run(organelle.run(handle)) {
+        assert!(e.to_string().contains("unable to stop"));
+    } else {
+        panic!("an error while stopping the nucleus should fail the organelle")
+    }
+}
+
+#[test]
+fn test_middleware_drops_everything() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let stops = Rc::new(RefCell::new(vec![]));
+
+    let mut organelle = Organelle::new(IdleSoma, handle.clone());
+
+    let drop_all = |_: Impulse<Synapse>| -> Option<Impulse<Synapse>> { None };
+
+    // the soma never sees start, but stop bypasses the middleware so run
+    // can still wait for it
+    organelle.add_soma_with_middleware(
+        RecordStop {
+            name: "soma",
+            stops: stops.clone(),
+        },
+        vec![Box::new(StopOnStart), Box::new(drop_all)],
+    );
+
+    core.run(organelle.run(handle)).unwrap();
+
+    assert_eq!(*stops.borrow(), vec!["soma"]);
+}
+
+#[test]
+fn test_rewire_running_organelle() {
+    let mut core = reactor::Core::new().unwrap();
+    let handle = core.handle();
+
+    let mut organelle = Organelle::new(IdleSoma, handle.clone());
+
+    let soma = organelle.add_soma(IdleSoma);
+
+    handle.spawn(
+        organelle
+            .impulse_sender()
+            .send(Impulse::RemoveTerminal(soma, Synapse::Link))
+            .map(|_| ())
+            .map_err(|_| ()),
+    );
+
+    if let Err(e) = core.run(organelle.run(handle)) {
+        match e.kind() {
+            &ErrorKind::InvalidSynapse(ref msg) => {
+                println!("got expected error: {}", *msg)
+            },
+            _ => panic!("unexpected error: {:#?}", e),
+        }
+    } else {
+        panic!("a running organelle should not accept RemoveTerminal")
+    }
+}